@@ -19,14 +19,14 @@ fn main() {
     println!("Players Max:     {}", &max_players);
     println!("Players Online:  {}", &players_online);
 
-    println!("");
+    println!();
 
     // Player Information
     for player in players.iter() {
         println!("Player: {:?}", player);
     }
 
-    println!("");
+    println!();
 
     // Rules
     for (rule, setting) in rules.iter() {
@@ -12,14 +12,58 @@
 //! let rules = server.rules().expect("Get server rules");
 //! ```
 
+pub use error::QueryError;
+pub use models::dayz::DayZMod;
+pub use models::dayz::DayZRules;
+pub use models::info::AppId;
+pub use models::info::ExtraData;
 pub use models::info::Info;
+pub use models::info::InfoBuilder;
+pub use models::info::InfoDelta;
+pub use models::info::InfoRef;
+pub use models::info::ModInfo;
 pub use models::info::Platform;
+pub use models::info::Protocol;
 pub use models::info::ServerType;
+pub use models::info::SourceTv;
+pub use models::info::TheShipInfo;
 pub use models::info::Vac;
 pub use models::info::Visibility;
 pub use models::Player;
+pub use models::ServerSnapshot;
+pub use protocol::ResponseKind;
+pub use request::RequestKind;
+pub use server::Engine;
+pub use server::Prefer;
+pub use server::QueryMeta;
 pub use server::Rules;
+pub use server::RulesDiff;
 pub use server::Server;
+/// Alias of [`Server`], for code that names the querying side a client.
+pub use server::Server as Client;
+pub use server::ServerBuilder;
+pub use transport::MockTransport;
+#[cfg(feature = "socks5")]
+pub use transport::Socks5Transport;
+pub use transport::Transport;
+pub use types::Encoding;
+
+/// Log the query lifecycle at debug level with the `log` crate, when the `log` feature is
+/// enabled. Expands to nothing otherwise.
+macro_rules! debug {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)+);
+    }};
+}
+
+/// Like `debug!`, at trace level.
+macro_rules! trace {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::trace!($($arg)+);
+    }};
+}
 
 #[allow(dead_code)]
 const ENCODING: &str = "utf-8";
@@ -29,9 +73,206 @@ const SIMPLE_RESPONSE_HEADER: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
 /// Packet is split.
 const MULTI_PACKET_RESPONSE_HEADER: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFE];
 
+pub mod error {
+
+    use std::fmt;
+    use std::io;
+
+    /// Errors that can occur while querying a server.
+    #[derive(Debug)]
+    pub enum QueryError {
+        /// An error occurred on the underlying socket.
+        Io(io::Error),
+        /// The server did not reply before the read timeout, which usually means it is down or
+        /// unreachable.
+        Timeout,
+        /// The socket reported the server unreachable, as when an ICMP port unreachable
+        /// message comes back because nothing is listening on the port.
+        ServerUnreachable(io::Error),
+        /// The address resolved, but not to an address of the preferred family.
+        NoMatchingAddress(String),
+        /// An address or address range could not be parsed.
+        InvalidAddress(String),
+        /// The response filled the entire receive buffer, so the datagram was likely truncated.
+        ResponseTruncated {
+            /// Size of the receive buffer that was filled.
+            buffer_size: usize,
+        },
+        /// The response header byte did not match the expected response type.
+        UnexpectedHeader {
+            /// Header byte for the expected response type.
+            expected: u8,
+            /// Header byte that was actually received.
+            found: u8,
+        },
+        /// A string was not null-terminated before the end of the data.
+        UnterminatedString,
+        /// A string ran past the maximum string length without a null terminator.
+        StringTooLong {
+            /// Maximum string length in bytes.
+            limit: usize,
+        },
+        /// A response declared or contained more rules or players than the configured maximum.
+        TooManyEntries {
+            /// Maximum number of entries.
+            limit: usize,
+        },
+        /// A string was not valid UTF-8, with [`Encoding::Utf8Strict`](crate::Encoding) set.
+        InvalidUtf8 {
+            /// Byte offset of the string.
+            position: usize,
+        },
+        /// The data ended before a value could be read.
+        UnexpectedEnd {
+            /// Byte offset of the value that could not be read.
+            position: usize,
+            /// Number of bytes the value needed.
+            needed: usize,
+        },
+        /// A byte held a value that is not defined for its field.
+        UnexpectedByte {
+            /// Name of the field.
+            field: &'static str,
+            /// Byte that was received.
+            byte: u8,
+            /// Byte offset of the field.
+            position: usize,
+        },
+        /// A split response stopped arriving before every packet was received.
+        IncompleteResponse {
+            /// Number of packets received.
+            received: u8,
+            /// Number of packets the server declared.
+            expected: u8,
+            /// Numbers of the packets that never arrived.
+            missing: Vec<u8>,
+            /// Number of payload bytes received before giving up.
+            assembled: usize,
+        },
+        /// A packet was too short or otherwise malformed.
+        MalformedPacket(String),
+        /// The server kept replying with challenges, more than the given number of times.
+        TooManyChallenges(u8),
+        /// The server replied with a challenge to a request sent without one.
+        ChallengeRequired,
+        /// The number of entries parsed did not match the count declared by the server.
+        CountMismatch {
+            /// Number of entries the server declared.
+            expected: usize,
+            /// Number of entries actually parsed.
+            found: usize,
+        },
+        /// The server hides its player list (as CS:GO does with `host_players_show` set to 0 or
+        /// 1), though its A2S_INFO response reports players online.
+        PlayersHidden {
+            /// Number of players reported by A2S_INFO.
+            reported: u8,
+        },
+        /// A split response grew beyond the configured maximum payload size.
+        PayloadTooLarge {
+            /// Maximum payload size in bytes.
+            limit: usize,
+        },
+        /// The server sent a bzip2 compressed split response, which is not supported.
+        CompressedResponse,
+        /// The query was cancelled through the cancel flag of the server.
+        Cancelled,
+    }
+
+    impl fmt::Display for QueryError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Io(e) => write!(f, "socket error: {e}"),
+                Self::Timeout => write!(f, "server did not reply before the read timeout"),
+                Self::ServerUnreachable(e) => write!(f, "server unreachable: {e}"),
+                Self::NoMatchingAddress(url) => {
+                    write!(f, "{url} did not resolve to an address of the preferred family")
+                }
+                Self::InvalidAddress(address) => write!(f, "invalid address: {address}"),
+                Self::ResponseTruncated { buffer_size } => write!(
+                    f,
+                    "response filled the {buffer_size} byte receive buffer and was likely truncated; use a larger buffer"
+                ),
+                Self::UnexpectedHeader { expected, found } => write!(
+                    f,
+                    "unexpected response header: expected {expected:#04X}, found {found:#04X}"
+                ),
+                Self::UnterminatedString => {
+                    write!(f, "string was not null-terminated before the end of the data")
+                }
+                Self::StringTooLong { limit } => {
+                    write!(f, "string exceeded the {limit} byte length limit")
+                }
+                Self::TooManyEntries { limit } => {
+                    write!(f, "response exceeded the limit of {limit} entries")
+                }
+                Self::InvalidUtf8 { position } => {
+                    write!(f, "string at byte {position} is not valid UTF-8")
+                }
+                Self::UnexpectedEnd { position, needed } => write!(
+                    f,
+                    "data ended at byte {position}, before a {needed} byte value"
+                ),
+                Self::UnexpectedByte {
+                    field,
+                    byte,
+                    position,
+                } => write!(f, "unexpected {field} {byte:#04X} at byte {position}"),
+                Self::IncompleteResponse {
+                    received,
+                    expected,
+                    missing,
+                    assembled,
+                } => write!(
+                    f,
+                    "split response incomplete: received {received} of {expected} packets ({assembled} bytes), missing {missing:?}"
+                ),
+                Self::MalformedPacket(reason) => write!(f, "malformed packet: {reason}"),
+                Self::TooManyChallenges(challenges) => {
+                    write!(f, "server replied with {challenges} challenges without answering")
+                }
+                Self::ChallengeRequired => write!(f, "server requires a challenge"),
+                Self::CountMismatch { expected, found } => write!(
+                    f,
+                    "server declared {expected} entries, but {found} were parsed"
+                ),
+                Self::PlayersHidden { reported } => write!(
+                    f,
+                    "player list hidden by server, which reports {reported} players online"
+                ),
+                Self::PayloadTooLarge { limit } => {
+                    write!(f, "split response exceeded the {limit} byte payload limit")
+                }
+                Self::CompressedResponse => {
+                    write!(f, "server sent a compressed split response, which is not supported")
+                }
+                Self::Cancelled => write!(f, "query cancelled"),
+            }
+        }
+    }
+
+    impl std::error::Error for QueryError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Io(e) | Self::ServerUnreachable(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<io::Error> for QueryError {
+        fn from(e: io::Error) -> Self {
+            Self::Io(e)
+        }
+    }
+}
+
 /// All types are little endian
 pub mod types {
 
+    use crate::error::QueryError;
+    use std::borrow::Cow;
+
     // All types are little endian
     pub type Byte = u8;
     pub type Short = i16;
@@ -40,7 +281,25 @@ pub mod types {
     pub type LongLong = u64;
     pub type CString = std::ffi::CString;
 
+    /// Default maximum length in bytes of a string read from a payload, without its null
+    /// terminator.
+    pub const DEFAULT_MAX_STRING_LEN: usize = 4 * 1024;
+
     /// All types are little endian,
+    ///
+    /// A value together with its wire type, for generic tooling such as packet dumps or
+    /// parsing game-specific fields from a schema.
+    ///
+    /// ```
+    /// use valve_server_query::types::{DataKind, DataType, PacketReader};
+    ///
+    /// let mut reader = PacketReader::new(&[0x87, 0x69, b'o', b'k', 0x00]);
+    /// let port = DataType::read(DataKind::Short, &mut reader).unwrap();
+    /// let name = DataType::read(DataKind::String, &mut reader).unwrap();
+    /// assert_eq!(port.to_string(), "short 27015");
+    /// assert_eq!(name.to_string(), "string \"ok\"");
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
     pub enum DataType {
         // Name   Description
         //
@@ -60,12 +319,99 @@ pub mod types {
         String(CString),
     }
 
+    /// Wire type of a [`DataType`], without a value.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum DataKind {
+        Byte,
+        Short,
+        Long,
+        Float,
+        LongLong,
+        String,
+    }
+
+    impl DataType {
+        /// Read a value of the given wire type.
+        pub fn read(kind: DataKind, reader: &mut PacketReader) -> Result<Self, QueryError> {
+            Ok(match kind {
+                DataKind::Byte => Self::Byte(reader.read_u8()?),
+                DataKind::Short => Self::Short(reader.read_i16()?),
+                DataKind::Long => Self::Long(reader.read_i32()?),
+                DataKind::Float => Self::Float(reader.read_f32()?),
+                DataKind::LongLong => Self::LongLong(reader.read_u64()?),
+                DataKind::String => Self::String(
+                    CString::new(reader.read_cstring_bytes()?)
+                        .expect("no null byte before the terminator"),
+                ),
+            })
+        }
+        /// Write the value in its wire format.
+        pub fn write(&self, bytes: &mut Vec<u8>) {
+            match self {
+                Self::Byte(value) => put_byte(bytes, *value),
+                Self::Short(value) => put_short(bytes, *value),
+                Self::Long(value) => put_long(bytes, *value),
+                Self::Float(value) => put_float(bytes, *value),
+                Self::LongLong(value) => put_longlong(bytes, *value),
+                Self::String(value) => bytes.extend_from_slice(value.as_bytes_with_nul()),
+            }
+        }
+        /// Wire type of the value.
+        pub fn kind(&self) -> DataKind {
+            match self {
+                Self::Byte(_) => DataKind::Byte,
+                Self::Short(_) => DataKind::Short,
+                Self::Long(_) => DataKind::Long,
+                Self::Float(_) => DataKind::Float,
+                Self::LongLong(_) => DataKind::LongLong,
+                Self::String(_) => DataKind::String,
+            }
+        }
+    }
+
+    impl std::fmt::Display for DataKind {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let name = match self {
+                Self::Byte => "byte",
+                Self::Short => "short",
+                Self::Long => "long",
+                Self::Float => "float",
+                Self::LongLong => "long long",
+                Self::String => "string",
+            };
+            f.write_str(name)
+        }
+    }
+
+    /// The wire type followed by the value, such as `short 27015` or `string "de_dust2"`.
+    impl std::fmt::Display for DataType {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} ", self.kind())?;
+            match self {
+                Self::Byte(value) => write!(f, "{value}"),
+                Self::Short(value) => write!(f, "{value}"),
+                Self::Long(value) => write!(f, "{value}"),
+                Self::Float(value) => write!(f, "{value}"),
+                Self::LongLong(value) => write!(f, "{value}"),
+                Self::String(value) => write!(f, "{:?}", value.to_string_lossy()),
+            }
+        }
+    }
+
+    #[deprecated(
+        since = "0.4.10",
+        note = "use `PacketReader::read_u8`, which fails rather than panics on short data"
+    )]
     pub fn get_byte<'a, I>(bytes: &mut I) -> Byte
     where
         I: Iterator<Item = &'a u8>,
     {
         bytes.next().expect("the next byte exists").to_owned()
     }
+    #[deprecated(
+        since = "0.4.10",
+        note = "use `PacketReader::read_i16`, which fails rather than panics on short data"
+    )]
     pub fn get_short<'a, I>(bytes: &mut I) -> Short
     where
         I: Iterator<Item = &'a u8>,
@@ -75,6 +421,10 @@ pub mod types {
             *bytes.next().expect("next byte exists"),
         ])
     }
+    #[deprecated(
+        since = "0.4.10",
+        note = "use `PacketReader::read_i32`, which fails rather than panics on short data"
+    )]
     pub fn get_long<'a, I>(bytes: &mut I) -> Long
     where
         I: Iterator<Item = &'a u8>,
@@ -86,6 +436,10 @@ pub mod types {
             *bytes.next().expect("next byte exists"),
         ])
     }
+    #[deprecated(
+        since = "0.4.10",
+        note = "use `PacketReader::read_f32`, which fails rather than panics on short data"
+    )]
     pub fn get_float<'a, I>(bytes: &mut I) -> Float
     where
         I: Iterator<Item = &'a u8>,
@@ -97,6 +451,10 @@ pub mod types {
             *bytes.next().expect("next byte exists"),
         ])
     }
+    #[deprecated(
+        since = "0.4.10",
+        note = "use `PacketReader::read_u64`, which fails rather than panics on short data"
+    )]
     pub fn get_longlong<'a, I>(bytes: &mut I) -> LongLong
     where
         I: Iterator<Item = &'a u8>,
@@ -112,33 +470,682 @@ pub mod types {
             *bytes.next().expect("next byte exists"),
         ])
     }
-    pub fn get_string<'a, I>(bytes: &mut I) -> String
+    /// Read a null-terminated UTF-8 string, replacing invalid sequences with U+FFFD.
+    ///
+    /// Reading stops at the end of the data, returning an error if no null terminator was found,
+    /// or after [`DEFAULT_MAX_STRING_LEN`] bytes, returning [`QueryError::StringTooLong`].
+    pub fn get_string<'a, I>(bytes: &mut I) -> Result<String, QueryError>
+    where
+        I: Iterator<Item = &'a u8>,
+    {
+        let raw = get_cstring(bytes)?;
+        Ok(String::from_utf8_lossy(raw.as_bytes()).into_owned())
+    }
+    /// Read a null-terminated string, keeping its raw bytes.
+    ///
+    /// Unlike [`get_string`], which decodes them as UTF-8, the bytes are left for the caller to
+    /// decode. Reading stops at the end of the data or the maximum length like [`get_string`].
+    pub fn get_cstring<'a, I>(bytes: &mut I) -> Result<CString, QueryError>
     where
         I: Iterator<Item = &'a u8>,
     {
-        let mut string = String::new();
+        let mut raw = Vec::new();
         loop {
-            let byte = bytes.next().expect("next byte exists");
+            let byte = bytes.next().ok_or(QueryError::UnterminatedString)?;
             if *byte == 0 {
                 break;
+            } else if raw.len() == DEFAULT_MAX_STRING_LEN {
+                return Err(QueryError::StringTooLong {
+                    limit: DEFAULT_MAX_STRING_LEN,
+                });
             } else {
-                string.push(*byte as char);
+                raw.push(*byte);
+            }
+        }
+        Ok(CString::new(raw).expect("no null byte before the terminator"))
+    }
+
+    pub fn put_byte(bytes: &mut Vec<u8>, value: Byte) {
+        bytes.push(value);
+    }
+    pub fn put_short(bytes: &mut Vec<u8>, value: Short) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_long(bytes: &mut Vec<u8>, value: Long) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_float(bytes: &mut Vec<u8>, value: Float) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_longlong(bytes: &mut Vec<u8>, value: LongLong) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    /// Write a string followed by its null terminator.
+    ///
+    /// The string should not contain a null byte itself, or it will be read back truncated.
+    pub fn put_cstring(bytes: &mut Vec<u8>, value: &str) {
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+    }
+
+    /// How the strings of a response are decoded.
+    ///
+    /// The protocol specifies UTF-8, but older GoldSource servers and some mods send names in
+    /// Windows-1252 instead, which the `encoding` feature can decode.
+    ///
+    /// Non-exhaustive, as the variants depend on the features enabled.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum Encoding {
+        /// UTF-8, failing with [`QueryError::InvalidUtf8`] on invalid sequences.
+        Utf8Strict,
+        /// UTF-8, replacing invalid sequences with U+FFFD.
+        #[default]
+        Utf8Lossy,
+        /// Windows-1252, the single-byte code page of western Windows installations.
+        #[cfg(feature = "encoding")]
+        Windows1252,
+        /// UTF-8 if the string is valid UTF-8, Windows-1252 otherwise.
+        #[cfg(feature = "encoding")]
+        AutoDetect,
+    }
+
+    impl Encoding {
+        /// Decode the bytes of a string, without its null terminator.
+        ///
+        /// Returns `None` if the bytes are invalid, which only happens with
+        /// [`Encoding::Utf8Strict`].
+        pub fn decode(self, bytes: &[u8]) -> Option<String> {
+            self.decode_borrowed(bytes).map(Cow::into_owned)
+        }
+        /// Like [`Encoding::decode`], borrowing from `bytes` rather than allocating when they
+        /// need no conversion, such as valid UTF-8 or ASCII.
+        pub fn decode_borrowed(self, bytes: &[u8]) -> Option<Cow<'_, str>> {
+            match self {
+                Self::Utf8Strict => std::str::from_utf8(bytes).ok().map(Cow::Borrowed),
+                Self::Utf8Lossy => Some(String::from_utf8_lossy(bytes)),
+                #[cfg(feature = "encoding")]
+                Self::Windows1252 => Some(
+                    encoding_rs::WINDOWS_1252
+                        .decode_without_bom_handling(bytes)
+                        .0,
+                ),
+                #[cfg(feature = "encoding")]
+                Self::AutoDetect => Self::Utf8Strict
+                    .decode_borrowed(bytes)
+                    .or_else(|| Self::Windows1252.decode_borrowed(bytes)),
+            }
+        }
+    }
+
+    /// Cursor over a packet, reading little endian values and keeping track of its position.
+    ///
+    /// Every read fails with [`QueryError::UnexpectedEnd`], giving the offset of the value,
+    /// rather than panicking when the packet is too short.
+    ///
+    /// ```
+    /// use valve_server_query::types::PacketReader;
+    ///
+    /// let mut reader = PacketReader::new(&[0x44, 0x02, 0x00, b'A', 0x00]);
+    /// assert_eq!(reader.read_u8().unwrap(), 0x44);
+    /// assert_eq!(reader.read_i16().unwrap(), 2);
+    /// assert_eq!(reader.read_cstring().unwrap(), "A");
+    /// assert_eq!(reader.remaining(), 0);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct PacketReader<'a> {
+        bytes: &'a [u8],
+        position: usize,
+        encoding: Encoding,
+        max_string_len: usize,
+    }
+
+    impl<'a> PacketReader<'a> {
+        pub fn new(bytes: &'a [u8]) -> Self {
+            Self {
+                bytes,
+                position: 0,
+                encoding: Encoding::default(),
+                max_string_len: DEFAULT_MAX_STRING_LEN,
+            }
+        }
+        /// Decode strings with `encoding` rather than lossy UTF-8.
+        pub fn with_encoding(self, encoding: Encoding) -> Self {
+            Self { encoding, ..self }
+        }
+        /// Fail on strings longer than `max_string_len` bytes, rather than
+        /// [`DEFAULT_MAX_STRING_LEN`].
+        pub fn with_max_string_len(self, max_string_len: usize) -> Self {
+            Self {
+                max_string_len,
+                ..self
+            }
+        }
+
+        /// Encoding strings are decoded with.
+        pub fn encoding(&self) -> Encoding {
+            self.encoding
+        }
+
+        /// Offset of the next byte to read.
+        pub fn position(&self) -> usize {
+            self.position
+        }
+        /// Number of bytes left to read.
+        pub fn remaining(&self) -> usize {
+            self.bytes.len() - self.position
+        }
+        /// The bytes left to read, without consuming them.
+        pub fn rest(&self) -> &'a [u8] {
+            &self.bytes[self.position..]
+        }
+        /// The next byte, without consuming it.
+        pub fn peek_u8(&self) -> Option<Byte> {
+            self.bytes.get(self.position).copied()
+        }
+
+        /// Read the next `count` bytes.
+        pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], QueryError> {
+            let bytes = self.rest().get(..count).ok_or(QueryError::UnexpectedEnd {
+                position: self.position,
+                needed: count,
+            })?;
+            self.position += count;
+            Ok(bytes)
+        }
+        fn read_array<const N: usize>(&mut self) -> Result<[u8; N], QueryError> {
+            let bytes = self.read_bytes(N)?;
+            Ok(bytes.try_into().expect("slice has the requested length"))
+        }
+
+        pub fn read_u8(&mut self) -> Result<Byte, QueryError> {
+            Ok(self.read_array::<1>()?[0])
+        }
+        pub fn read_i16(&mut self) -> Result<Short, QueryError> {
+            Ok(Short::from_le_bytes(self.read_array()?))
+        }
+        pub fn read_i32(&mut self) -> Result<Long, QueryError> {
+            Ok(Long::from_le_bytes(self.read_array()?))
+        }
+        pub fn read_f32(&mut self) -> Result<Float, QueryError> {
+            Ok(Float::from_le_bytes(self.read_array()?))
+        }
+        pub fn read_u64(&mut self) -> Result<LongLong, QueryError> {
+            Ok(LongLong::from_le_bytes(self.read_array()?))
+        }
+        /// Read a null-terminated string, consuming the terminator, and decode it with the
+        /// encoding of the reader.
+        pub fn read_cstring(&mut self) -> Result<String, QueryError> {
+            self.read_cstr().map(Cow::into_owned)
+        }
+        /// Like [`PacketReader::read_cstring`], borrowing the string from the packet when it
+        /// needs no conversion.
+        pub fn read_cstr(&mut self) -> Result<Cow<'a, str>, QueryError> {
+            let position = self.position;
+            let bytes = self.read_cstring_bytes()?;
+            self.encoding
+                .decode_borrowed(bytes)
+                .ok_or(QueryError::InvalidUtf8 { position })
+        }
+        /// Read the raw bytes of a null-terminated string, consuming but not including the
+        /// terminator.
+        ///
+        /// Fails with [`QueryError::StringTooLong`] if the string is longer than the maximum
+        /// string length.
+        pub fn read_cstring_bytes(&mut self) -> Result<&'a [u8], QueryError> {
+            let rest = self.rest();
+            let searched = &rest[..rest.len().min(self.max_string_len.saturating_add(1))];
+            let length = match searched.iter().position(|byte| *byte == 0) {
+                Some(length) => length,
+                None if searched.len() < rest.len() => {
+                    return Err(QueryError::StringTooLong {
+                        limit: self.max_string_len,
+                    })
+                }
+                None => return Err(QueryError::UnterminatedString),
+            };
+            let bytes = self.read_bytes(length)?;
+            self.position += 1;
+            Ok(bytes)
+        }
+    }
+
+    /// Builder of an outgoing packet, the counterpart of [`PacketReader`].
+    ///
+    /// ```
+    /// use valve_server_query::types::PacketWriter;
+    ///
+    /// let request = PacketWriter::new()
+    ///     .write_simple_header()
+    ///     .write_u8(0x55)
+    ///     .write_challenge(&[0xFF; 4])
+    ///     .into_bytes();
+    /// assert_eq!(request, [0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF]);
+    /// ```
+    #[derive(Debug, Clone, Default)]
+    pub struct PacketWriter {
+        bytes: Vec<u8>,
+    }
+
+    impl PacketWriter {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Write the `0xFFFFFFFF` header of a single packet.
+        pub fn write_simple_header(self) -> Self {
+            self.write_bytes(&crate::SIMPLE_RESPONSE_HEADER)
+        }
+        pub fn write_u8(mut self, value: Byte) -> Self {
+            self.bytes.push(value);
+            self
+        }
+        pub fn write_bytes(mut self, bytes: &[u8]) -> Self {
+            self.bytes.extend_from_slice(bytes);
+            self
+        }
+        /// Write a string followed by its null terminator.
+        pub fn write_cstring(self, value: &str) -> Self {
+            self.write_bytes(value.as_bytes()).write_u8(0)
+        }
+        /// Write a challenge number as received from the server.
+        pub fn write_challenge(self, challenge: &[u8]) -> Self {
+            self.write_bytes(challenge)
+        }
+
+        /// The bytes written so far.
+        pub fn into_bytes(self) -> Vec<u8> {
+            self.bytes
+        }
+        /// The bytes written so far, without consuming the writer.
+        pub fn as_bytes(&self) -> &[u8] {
+            &self.bytes
+        }
+    }
+
+    /// Lets a writer be sent directly, as by [`Transport::send_to`](crate::Transport).
+    impl AsRef<[u8]> for PacketWriter {
+        fn as_ref(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl From<PacketWriter> for Vec<u8> {
+        fn from(writer: PacketWriter) -> Self {
+            writer.into_bytes()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_put_get_round_trip() {
+            let mut bytes = Vec::new();
+            for value in [0, 1, 0x7F, 0x80, Byte::MAX] {
+                put_byte(&mut bytes, value);
+                assert_eq!(get_byte(&mut bytes.iter()), value);
+                bytes.clear();
+            }
+            for value in [0, 1, -1, 27015, Short::MIN, Short::MAX] {
+                put_short(&mut bytes, value);
+                assert_eq!(get_short(&mut bytes.iter()), value);
+                bytes.clear();
+            }
+            for value in [0, 1, -1, 0x0D0C_0B0A, Long::MIN, Long::MAX] {
+                put_long(&mut bytes, value);
+                assert_eq!(get_long(&mut bytes.iter()), value);
+                bytes.clear();
+            }
+            for value in [
+                0.0,
+                -0.0,
+                61.5,
+                -1.0,
+                Float::MIN_POSITIVE,
+                Float::MAX,
+                Float::INFINITY,
+                Float::NAN,
+            ] {
+                put_float(&mut bytes, value);
+                assert_eq!(get_float(&mut bytes.iter()).to_bits(), value.to_bits());
+                bytes.clear();
+            }
+            for value in [0, 1, 76_561_198_000_000_000, LongLong::MAX] {
+                put_longlong(&mut bytes, value);
+                assert_eq!(get_longlong(&mut bytes.iter()), value);
+                bytes.clear();
+            }
+            for value in ["", "de_dust2", "Counter-Strike: Source"] {
+                put_cstring(&mut bytes, value);
+                assert_eq!(get_string(&mut bytes.iter()).unwrap(), value);
+                bytes.clear();
+            }
+
+            // Values are written one after another
+            put_short(&mut bytes, 2);
+            put_cstring(&mut bytes, "A");
+            put_long(&mut bytes, -1);
+            assert_eq!(bytes, [0x02, 0x00, b'A', 0x00, 0xFF, 0xFF, 0xFF, 0xFF]);
+        }
+
+        #[test]
+        fn test_get_string_utf8() {
+            let mut bytes = Vec::new();
+            put_cstring(&mut bytes, "サーバー");
+            put_cstring(&mut bytes, "Привет");
+            bytes.extend(b"bad \xFF byte\x00");
+
+            let mut iter = bytes.iter();
+            assert_eq!(get_string(&mut iter).unwrap(), "サーバー");
+            assert_eq!(get_string(&mut iter).unwrap(), "Привет");
+            assert_eq!(get_string(&mut iter).unwrap(), "bad \u{FFFD} byte");
+
+            let mut reader = PacketReader::new(&bytes);
+            assert_eq!(reader.read_cstring().unwrap(), "サーバー");
+            assert_eq!(reader.read_cstring().unwrap(), "Привет");
+            assert_eq!(reader.read_cstring().unwrap(), "bad \u{FFFD} byte");
+        }
+
+        #[test]
+        fn test_encoding_decode() {
+            let utf8 = "José".as_bytes();
+            let windows_1252 = b"Jos\xE9";
+
+            assert_eq!(Encoding::Utf8Strict.decode(utf8).unwrap(), "José");
+            assert_eq!(Encoding::Utf8Strict.decode(windows_1252), None);
+            assert_eq!(
+                Encoding::Utf8Lossy.decode(windows_1252).unwrap(),
+                "Jos\u{FFFD}"
+            );
+
+            let mut reader =
+                PacketReader::new(b"ok\x00Jos\xE9\x00").with_encoding(Encoding::Utf8Strict);
+            assert_eq!(reader.read_cstring().unwrap(), "ok");
+            assert!(matches!(
+                reader.read_cstring(),
+                Err(QueryError::InvalidUtf8 { position: 3 })
+            ));
+        }
+
+        #[cfg(feature = "encoding")]
+        #[test]
+        fn test_encoding_decode_windows_1252() {
+            let utf8 = "José".as_bytes();
+            let windows_1252 = b"Jos\xE9 \x80";
+
+            assert_eq!(
+                Encoding::Windows1252.decode(windows_1252).unwrap(),
+                "José €"
+            );
+            assert_eq!(Encoding::AutoDetect.decode(windows_1252).unwrap(), "José €");
+            assert_eq!(Encoding::AutoDetect.decode(utf8).unwrap(), "José");
+        }
+
+        #[test]
+        fn test_max_string_len() {
+            let mut reader = PacketReader::new(b"abc\x00abcd\x00").with_max_string_len(3);
+            assert_eq!(reader.read_cstring().unwrap(), "abc");
+            assert!(matches!(
+                reader.read_cstring(),
+                Err(QueryError::StringTooLong { limit: 3 })
+            ));
+            assert!(matches!(
+                PacketReader::new(b"abc")
+                    .with_max_string_len(3)
+                    .read_cstring(),
+                Err(QueryError::UnterminatedString)
+            ));
+
+            let mut long = vec![b'a'; DEFAULT_MAX_STRING_LEN];
+            long.push(0);
+            assert_eq!(
+                get_string(&mut long.iter()).unwrap().len(),
+                DEFAULT_MAX_STRING_LEN
+            );
+            assert_eq!(
+                PacketReader::new(&long).read_cstring().unwrap().len(),
+                DEFAULT_MAX_STRING_LEN
+            );
+
+            long.insert(0, b'a');
+            assert!(matches!(
+                get_string(&mut long.iter()),
+                Err(QueryError::StringTooLong {
+                    limit: DEFAULT_MAX_STRING_LEN
+                })
+            ));
+            assert!(matches!(
+                PacketReader::new(&long).read_cstring(),
+                Err(QueryError::StringTooLong {
+                    limit: DEFAULT_MAX_STRING_LEN
+                })
+            ));
+        }
+
+        #[test]
+        fn test_data_type_round_trip() {
+            let values = [
+                DataType::Byte(0x49),
+                DataType::Short(-2),
+                DataType::Long(27015),
+                DataType::Float(1.5),
+                DataType::LongLong(76_561_198_000_000_000),
+                DataType::String(CString::new("de_dust2").unwrap()),
+            ];
+
+            let mut bytes = Vec::new();
+            for value in &values {
+                value.write(&mut bytes);
+            }
+            assert_eq!(bytes.len(), 1 + 2 + 4 + 4 + 8 + 9);
+
+            let mut reader = PacketReader::new(&bytes);
+            for value in &values {
+                assert_eq!(&DataType::read(value.kind(), &mut reader).unwrap(), value);
             }
+            assert_eq!(reader.remaining(), 0);
+
+            let displayed: Vec<String> = values.iter().map(ToString::to_string).collect();
+            assert_eq!(
+                displayed,
+                [
+                    "byte 73",
+                    "short -2",
+                    "long 27015",
+                    "float 1.5",
+                    "long long 76561198000000000",
+                    "string \"de_dust2\"",
+                ]
+            );
+            assert!(matches!(
+                DataType::read(DataKind::Long, &mut PacketReader::new(&[0x01])),
+                Err(QueryError::UnexpectedEnd {
+                    position: 0,
+                    needed: 4
+                })
+            ));
+        }
+
+        #[test]
+        fn test_get_cstring() {
+            let bytes = b"Zo\xC3\xAB\x00rest";
+            let mut iter = bytes.iter();
+            assert_eq!(get_cstring(&mut iter).unwrap().as_bytes(), b"Zo\xC3\xAB");
+            assert_eq!(iter.as_slice(), b"rest");
+
+            assert!(matches!(
+                get_cstring(&mut b"no terminator".iter()),
+                Err(QueryError::UnterminatedString)
+            ));
+
+            let mut reader = PacketReader::new(bytes);
+            assert_eq!(reader.read_cstring_bytes().unwrap(), b"Zo\xC3\xAB");
+            assert_eq!(reader.rest(), b"rest");
+        }
+
+        #[test]
+        fn test_packet_writer() {
+            let bytes = PacketWriter::new()
+                .write_simple_header()
+                .write_u8(0x54)
+                .write_cstring("Hi")
+                .write_challenge(&[1, 2, 3, 4])
+                .into_bytes();
+            assert_eq!(
+                bytes,
+                [0xFF, 0xFF, 0xFF, 0xFF, 0x54, b'H', b'i', 0x00, 1, 2, 3, 4]
+            );
+
+            let writer = PacketWriter::new().write_simple_header().write_u8(0x57);
+            assert_eq!(writer.as_ref(), [0xFF, 0xFF, 0xFF, 0xFF, 0x57]);
+            assert_eq!(writer.as_bytes(), crate::request::get_challenge());
+            assert_eq!(Vec::from(writer), crate::request::get_challenge());
+        }
+
+        #[test]
+        fn test_packet_reader() {
+            let mut bytes = vec![0x49];
+            bytes.extend((-2i16).to_le_bytes());
+            bytes.extend(27015i32.to_le_bytes());
+            bytes.extend(1.5f32.to_le_bytes());
+            bytes.extend(76_561_198_000_000_000u64.to_le_bytes());
+            bytes.extend(b"name\x00");
+
+            let mut reader = PacketReader::new(&bytes);
+            assert_eq!(reader.peek_u8(), Some(0x49));
+            assert_eq!(reader.read_u8().unwrap(), 0x49);
+            assert_eq!(reader.read_i16().unwrap(), -2);
+            assert_eq!(reader.read_i32().unwrap(), 27015);
+            assert_eq!(reader.read_f32().unwrap(), 1.5);
+            assert_eq!(reader.position(), 11);
+            assert_eq!(reader.read_u64().unwrap(), 76_561_198_000_000_000);
+            assert_eq!(reader.remaining(), 5);
+            assert_eq!(reader.read_cstring().unwrap(), "name");
+            assert_eq!(reader.remaining(), 0);
+            assert_eq!(reader.peek_u8(), None);
+        }
+
+        #[test]
+        fn test_packet_reader_end_of_buffer() {
+            let mut reader = PacketReader::new(&[0x01, 0x02, 0x03]);
+            assert_eq!(reader.read_u8().unwrap(), 0x01);
+            assert!(matches!(
+                reader.read_i32(),
+                Err(QueryError::UnexpectedEnd {
+                    position: 1,
+                    needed: 4
+                })
+            ));
+            // A failed read consumes nothing
+            assert_eq!(reader.position(), 1);
+            assert!(matches!(
+                reader.read_u64(),
+                Err(QueryError::UnexpectedEnd { needed: 8, .. })
+            ));
+            assert!(matches!(
+                reader.read_f32(),
+                Err(QueryError::UnexpectedEnd { needed: 4, .. })
+            ));
+            assert!(matches!(
+                reader.read_cstring(),
+                Err(QueryError::UnterminatedString)
+            ));
+            assert_eq!(reader.read_i16().unwrap(), 0x0302);
+            assert!(matches!(
+                reader.read_u8(),
+                Err(QueryError::UnexpectedEnd {
+                    position: 3,
+                    needed: 1
+                })
+            ));
         }
-        string
     }
 }
 
 pub mod models {
 
-    use crate::types::{get_byte, get_float, get_long, get_string, Byte, Float, Long};
+    use crate::error::QueryError;
+    use crate::models::info::Info;
+    use crate::protocol::ResponseKind;
+    use crate::server::Rules;
+    use crate::types::{
+        get_cstring, put_byte, put_float, put_long, Byte, Float, Long, PacketReader,
+    };
+    use std::cmp::Ordering;
+    use std::time::Duration;
 
+    /// A player in an A2S_PLAYER response.
+    ///
+    /// With the `serde` feature, [`Player::name_bytes`] is only serialized when the name did
+    /// not decode to exactly those bytes.
     #[derive(Debug, PartialEq, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(from = "PlayerFields", into = "PlayerFields"))]
     pub struct Player {
         index: Byte,
         name: String,
+        /// Name as sent by the server, before decoding.
+        name_bytes: Vec<u8>,
+        score: Long,
+        duration: Float,
+        /// Number of deaths (The Ship only).
+        deaths: Option<Long>,
+        /// Amount of money (The Ship only).
+        money: Option<Long>,
+    }
+
+    /// Fields of a [`Player`] as serialized.
+    #[cfg(feature = "serde")]
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct PlayerFields {
+        index: Byte,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name_bytes: Option<Vec<u8>>,
         score: Long,
         duration: Float,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deaths: Option<Long>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        money: Option<Long>,
+    }
+
+    #[cfg(feature = "serde")]
+    impl From<Player> for PlayerFields {
+        fn from(player: Player) -> Self {
+            let name_bytes =
+                (player.name_bytes != player.name.as_bytes()).then_some(player.name_bytes);
+            Self {
+                index: player.index,
+                name: player.name,
+                name_bytes,
+                score: player.score,
+                duration: player.duration,
+                deaths: player.deaths,
+                money: player.money,
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl From<PlayerFields> for Player {
+        fn from(fields: PlayerFields) -> Self {
+            let name_bytes = fields
+                .name_bytes
+                .unwrap_or_else(|| fields.name.as_bytes().to_vec());
+            Self {
+                index: fields.index,
+                name: fields.name,
+                name_bytes,
+                score: fields.score,
+                duration: fields.duration,
+                deaths: fields.deaths,
+                money: fields.money,
+            }
+        }
     }
 
     impl Default for Player {
@@ -146,97 +1153,454 @@ pub mod models {
             Self {
                 index: 0,
                 name: "".to_string(),
+                name_bytes: Vec::new(),
                 score: 0,
                 duration: 0.0,
+                deaths: None,
+                money: None,
+            }
+        }
+    }
+
+    impl Player {
+        /// Create a player record, such as for a fixture to serialize with
+        /// [`Player::to_bytes`].
+        pub fn new(index: Byte, name: &str, score: Long, duration: Float) -> Self {
+            Self {
+                index,
+                name: name.to_string(),
+                name_bytes: name.as_bytes().to_vec(),
+                score,
+                duration,
+                ..Default::default()
+            }
+        }
+        /// Add The Ship's deaths and money to the player record.
+        pub fn with_the_ship(self, deaths: Long, money: Long) -> Self {
+            Self {
+                deaths: Some(deaths),
+                money: Some(money),
+                ..self
             }
         }
     }
 
     /// Getters (Immutable)
     impl Player {
+        /// Index of the player chunk in the response.
+        pub fn index(&self) -> Byte {
+            self.index
+        }
+        /// Name of the player.
+        ///
+        /// Decoded with the encoding of the reader it was parsed from, lossy UTF-8 by default;
+        /// see [`Player::name_bytes`] to decode it differently.
         pub fn name(&self) -> &str {
             &self.name
         }
+        /// Name of the player as sent by the server, without the null terminator.
+        ///
+        /// Names often contain UTF-8 multibyte sequences and engine color codes, which are
+        /// kept intact here.
+        pub fn name_bytes(&self) -> &[u8] {
+            &self.name_bytes
+        }
+        /// Player's score (usually "frags" or "kills").
         pub fn score(&self) -> Long {
             self.score
         }
+        /// Time (in seconds) the player has been connected to the server.
         pub fn duration(&self) -> Float {
             self.duration
         }
+        /// Time the player has been connected to the server.
+        ///
+        /// A negative, NaN or otherwise unrepresentable duration is clamped to zero.
+        pub fn connected_duration(&self) -> Duration {
+            Duration::try_from_secs_f32(self.duration).unwrap_or(Duration::ZERO)
+        }
+    }
+
+    impl Player {
+        /// Scoreboard order: highest score first, then by name.
+        ///
+        /// `Player` does not implement `Ord`, as its `f32` duration keeps it from being `Eq`.
+        ///
+        /// ```
+        /// # fn scoreboard(mut players: Vec<valve_server_query::Player>) {
+        /// players.sort_by(|a, b| a.cmp_by_score(b));
+        /// # }
+        /// ```
+        pub fn cmp_by_score(&self, other: &Self) -> Ordering {
+            other
+                .score
+                .cmp(&self.score)
+                .then_with(|| self.name.cmp(&other.name))
+        }
+        /// Number of deaths, only sent by servers running The Ship.
+        pub fn deaths(&self) -> Option<Long> {
+            self.deaths
+        }
+        /// Amount of money, only sent by servers running The Ship.
+        pub fn money(&self) -> Option<Long> {
+            self.money
+        }
     }
 
     impl Player {
-        pub fn get_players(bytes: &[u8]) -> Vec<Self> {
-            let mut it = bytes.iter();
+        /// Size of the smallest player record: index, empty name, score and duration.
+        const MIN_RECORD_LEN: usize = std::mem::size_of::<Byte>()
+            + 1
+            + std::mem::size_of::<Long>()
+            + std::mem::size_of::<Float>();
+
+        /// Parse the player records following the player count of an A2S_PLAYER payload,
+        /// until the bytes run out.
+        ///
+        /// The count is not known here; see [`parse_players`](crate::protocol::parse_players) to
+        /// parse a whole response, as many players as it declares.
+        pub fn get_players(bytes: &[u8]) -> Result<Vec<Self>, QueryError> {
+            Self::iter_from_bytes(bytes).collect()
+        }
+
+        /// Parse the player records of an A2S_PLAYER payload lazily, one per step.
+        ///
+        /// Stops once too few bytes remain for another record, or after yielding the first
+        /// error.
+        pub fn iter_from_bytes(
+            bytes: &[u8],
+        ) -> impl Iterator<Item = Result<Self, QueryError>> + '_ {
+            let mut reader = PacketReader::new(bytes);
+            let mut failed = false;
+
+            std::iter::from_fn(move || {
+                let more = reader.remaining() >= Self::MIN_RECORD_LEN;
+                if failed || !more {
+                    return None;
+                }
+
+                let player = Self::from_reader(&mut reader);
+                failed = player.is_err();
+                Some(player)
+            })
+        }
+
+        /// Like [`Player::get_players`], for The Ship (AppID 2400), whose player records end
+        /// with the deaths and money of the player.
+        pub fn get_players_the_ship(bytes: &[u8]) -> Result<Vec<Self>, QueryError> {
+            let mut reader = PacketReader::new(bytes);
             let mut players: Vec<Self> = Vec::new();
 
-            while it.len()
-                > (
-                    // There's a String too, but that has a varialble size.
-                    std::mem::size_of::<Byte>()
-                        + std::mem::size_of::<Long>()
-                        + std::mem::size_of::<Float>()
-                )
+            while reader.remaining()
+                >= Self::MIN_RECORD_LEN + std::mem::size_of::<Long>() + std::mem::size_of::<Long>()
             {
-                let player = Self::from_iter_bytes(&mut it);
+                let player = Self::from_reader_the_ship(&mut reader)?;
 
                 players.push(player);
             }
 
-            players
+            Ok(players)
         }
 
-        pub fn from_iter_bytes<'a, I>(iter_bytes: &mut I) -> Self
-        where
-            I: Iterator<Item = &'a u8>,
-        {
-            let index = get_byte(iter_bytes);
-            let name = get_string(iter_bytes);
-            let score = get_long(iter_bytes);
-            let duration = get_float(iter_bytes);
-
-            Self {
+        /// Parse a player record from a reader positioned at its index.
+        pub fn from_reader(reader: &mut PacketReader) -> Result<Self, QueryError> {
+            let index = reader.read_u8()?;
+            let position = reader.position();
+            let name_bytes = reader.read_cstring_bytes()?.to_vec();
+            let name = reader
+                .encoding()
+                .decode(&name_bytes)
+                .ok_or(QueryError::InvalidUtf8 { position })?;
+            let score = reader.read_i32()?;
+            let duration = reader.read_f32()?;
+
+            Ok(Self {
                 index,
                 name,
+                name_bytes,
                 score,
                 duration,
-            }
+                ..Default::default()
+            })
         }
 
-        pub fn from_bytes(bytes: &[u8]) -> Self {
-            let mut it = bytes.iter();
-
-            let index = get_byte(&mut it);
-            let name = get_string(&mut it);
-            let score = get_long(&mut it);
-            let duration = get_float(&mut it);
+        /// Parse a player record from The Ship, which carries the deaths and money of the
+        /// player after the common fields.
+        pub fn from_reader_the_ship(reader: &mut PacketReader) -> Result<Self, QueryError> {
+            let player = Self::from_reader(reader)?;
+            let deaths = reader.read_i32()?;
+            let money = reader.read_i32()?;
+
+            Ok(Self {
+                deaths: Some(deaths),
+                money: Some(money),
+                ..player
+            })
+        }
 
-            Self {
+        /// Parse a player record from an iterator over its bytes.
+        ///
+        /// Fails with [`QueryError::UnexpectedEnd`] if the data ends within a numeric field,
+        /// giving its offset from the start of the record.
+        pub fn from_iter_bytes<'a, I>(iter_bytes: &mut I) -> Result<Self, QueryError>
+        where
+            I: Iterator<Item = &'a u8>,
+        {
+            let mut position = 0;
+            let [index] = next_bytes(iter_bytes, &mut position)?;
+            let name_bytes = get_cstring(iter_bytes)?.into_bytes();
+            position += name_bytes.len() + 1;
+            let score = Long::from_le_bytes(next_bytes(iter_bytes, &mut position)?);
+            let duration = Float::from_le_bytes(next_bytes(iter_bytes, &mut position)?);
+
+            Ok(Self {
                 index,
-                name,
+                name: String::from_utf8_lossy(&name_bytes).into_owned(),
+                name_bytes,
                 score,
                 duration,
-            }
+                ..Default::default()
+            })
+        }
+
+        /// Parse a player record from The Ship, which carries the deaths and money of the
+        /// player after the common fields.
+        pub fn from_iter_bytes_the_ship<'a, I>(iter_bytes: &mut I) -> Result<Self, QueryError>
+        where
+            I: Iterator<Item = &'a u8>,
+        {
+            let player = Self::from_iter_bytes(iter_bytes)?;
+            let mut position = player.name_bytes.len() + 10;
+            let deaths = Long::from_le_bytes(next_bytes(iter_bytes, &mut position)?);
+            let money = Long::from_le_bytes(next_bytes(iter_bytes, &mut position)?);
+
+            Ok(Self {
+                deaths: Some(deaths),
+                money: Some(money),
+                ..player
+            })
+        }
+
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, QueryError> {
+            Ok(Self::parse(bytes)?.0)
+        }
+
+        /// Parse a player record like [`Player::from_bytes`], also returning the number of
+        /// bytes it took, where the next record begins.
+        pub fn parse(bytes: &[u8]) -> Result<(Self, usize), QueryError> {
+            let mut reader = PacketReader::new(bytes);
+            let player = Self::from_reader(&mut reader)?;
+            Ok((player, reader.position()))
+        }
+
+        /// Serialize the player record, the inverse of [`Player::from_bytes`].
+        ///
+        /// The deaths and money of The Ship follow the common fields when present.
+        pub fn to_bytes(&self) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            put_byte(&mut bytes, self.index);
+            bytes.extend_from_slice(&self.name_bytes);
+            put_byte(&mut bytes, 0);
+            put_long(&mut bytes, self.score);
+            put_float(&mut bytes, self.duration);
+            if let (Some(deaths), Some(money)) = (self.deaths, self.money) {
+                put_long(&mut bytes, deaths);
+                put_long(&mut bytes, money);
+            }
+            bytes
+        }
+    }
+
+    /// Read the next `N` bytes of a record, `position` bytes into it.
+    fn next_bytes<'a, I, const N: usize>(
+        iter_bytes: &mut I,
+        position: &mut usize,
+    ) -> Result<[u8; N], QueryError>
+    where
+        I: Iterator<Item = &'a u8>,
+    {
+        let mut bytes = [0; N];
+        for byte in &mut bytes {
+            *byte = *iter_bytes.next().ok_or(QueryError::UnexpectedEnd {
+                position: *position,
+                needed: N,
+            })?;
+        }
+        *position += N;
+        Ok(bytes)
+    }
+
+    /// Responses of a server to each query, taken together, such as to store its history.
+    ///
+    /// With the `postcard` feature, a snapshot converts to and from a compact binary form.
+    #[derive(Debug, PartialEq, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ServerSnapshot {
+        #[cfg_attr(feature = "serde", serde(with = "compact::info"))]
+        pub info: Info,
+        #[cfg_attr(feature = "serde", serde(with = "compact::players"))]
+        pub players: Vec<Player>,
+        /// `None` if the server did not answer A2S_RULES, as many do not.
+        pub rules: Option<Rules>,
+    }
+
+    /// Snapshot fields in a form binary formats can read back.
+    ///
+    /// Fields that are `None` are skipped in human-readable formats such as JSON, which a
+    /// binary format without field names cannot tell apart from the next field. In those,
+    /// the info is stored as its A2S_INFO payload, and the players as tuples of their fields.
+    #[cfg(feature = "serde")]
+    mod compact {
+        pub mod info {
+            use crate::models::Info;
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            pub fn serialize<S: Serializer>(info: &Info, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    info.serialize(serializer)
+                } else {
+                    info.to_bytes().serialize(serializer)
+                }
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Info, D::Error> {
+                if deserializer.is_human_readable() {
+                    Info::deserialize(deserializer)
+                } else {
+                    let bytes = Vec::<u8>::deserialize(deserializer)?;
+                    Info::from_bytes(&bytes).map_err(serde::de::Error::custom)
+                }
+            }
+        }
+
+        pub mod players {
+            use crate::models::Player;
+            use crate::types::{Byte, Float, Long};
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            type Fields = (
+                Byte,
+                String,
+                Vec<u8>,
+                Long,
+                Float,
+                Option<Long>,
+                Option<Long>,
+            );
+
+            pub fn serialize<S: Serializer>(
+                players: &[Player],
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    players.serialize(serializer)
+                } else {
+                    serializer.collect_seq(players.iter().map(|player| {
+                        (
+                            player.index,
+                            &player.name,
+                            &player.name_bytes,
+                            player.score,
+                            player.duration,
+                            player.deaths,
+                            player.money,
+                        )
+                    }))
+                }
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<Player>, D::Error> {
+                if deserializer.is_human_readable() {
+                    return Vec::deserialize(deserializer);
+                }
+                let players = Vec::<Fields>::deserialize(deserializer)?;
+                Ok(players
+                    .into_iter()
+                    .map(
+                        |(index, name, name_bytes, score, duration, deaths, money)| Player {
+                            index,
+                            name,
+                            name_bytes,
+                            score,
+                            duration,
+                            deaths,
+                            money,
+                        },
+                    )
+                    .collect())
+            }
+        }
+    }
+
+    #[cfg(feature = "postcard")]
+    impl ServerSnapshot {
+        /// Serialize the snapshot with [postcard](https://docs.rs/postcard).
+        pub fn to_bytes(&self) -> Vec<u8> {
+            postcard::to_allocvec(self).expect("snapshot serializes to a Vec")
+        }
+
+        /// Deserialize a snapshot from the bytes of [`ServerSnapshot::to_bytes`].
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+            postcard::from_bytes(bytes)
         }
     }
 
+    /// Serialize an A2S_PLAYER payload: the header, the player count, then the player records.
+    ///
+    /// The payload starts at the header byte, without the simple response header. Only the
+    /// first 255 players are written, the most the count can declare.
+    pub fn players_to_payload(players: &[Player]) -> Vec<u8> {
+        let players = &players[..players.len().min(Byte::MAX as usize)];
+        let mut payload = vec![ResponseKind::Players.as_byte(), players.len() as Byte];
+        for player in players {
+            payload.extend(player.to_bytes());
+        }
+        payload
+    }
+
     pub mod info {
 
-        use crate::types::{Byte, LongLong, Short};
+        use crate::error::QueryError;
+        use crate::protocol::ResponseKind;
+        use crate::types::{
+            put_byte, put_cstring, put_long, put_longlong, put_short, Byte, Long, LongLong,
+            PacketReader, Short,
+        };
+        use std::borrow::Cow;
+        use std::fmt;
+        use std::net::SocketAddr;
 
         /// Represents a steam game server.
         ///
         /// Ref: <https://developer.valvesoftware.com/wiki/Server_queries#A2S_INFO>
         ///
-        /// ```compile_fail
+        /// ```no_run
+        /// # use valve_server_query::Server;
+        /// # let server = Server::new("127.0.0.1:12345").unwrap();
+        /// let info = server.info().expect("Get general server information");
+        ///
         /// let server_name = info.name();
         /// let loaded_map = info.map();
         /// let max_players = info.player_max();
         /// let players_online = info.player_count();
         /// ```
-        #[derive(Debug, PartialEq, Clone)]
+        ///
+        /// Equality and hashing take every field into account, including the Extra Data Flag
+        /// and the [trailing bytes](Info::trailing_bytes): two responses are equal only if
+        /// they hold the same information.
+        ///
+        /// With the `serde` feature, fields are named after their getters, and those that are
+        /// `None` are left out. Deserializing checks that the fields present agree with the
+        /// protocol, AppID and Extra Data Flag, as they would in a response.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(try_from = "InfoFields", into = "InfoFields"))]
         pub struct Info {
-            /// Response header. Always equal to 'I' (0x49).
+            /// Response header. 'I' (0x49), or 'm' (0x6D) for the obsolete GoldSource response.
             header: Byte,
             /// Protocol version used by the server.
             protocol: Byte,
@@ -274,6 +1638,8 @@ pub mod models {
             /// 0 for unsecured
             /// 1 for secured
             vac: Vac,
+            /// Game mode and settings of The Ship (AppID 2400) servers.
+            the_ship: Option<TheShipInfo>,
             /// Version of the game installed on the server.
             game_version: String,
             /// Flag for Extra Features
@@ -294,101 +1660,389 @@ pub mod models {
             game_id: Option<LongLong>,
             /// Trailing bytes for Self::from_bytes
             trailing_bytes: Option<Vec<Byte>>,
+            /// IP address and port of the server (obsolete GoldSource response only).
+            address: Option<String>,
+            /// Information about the mod the server runs (obsolete GoldSource response only).
+            mod_info: Option<ModInfo>,
         }
 
-        impl Info {
-            pub fn from_bytes(bytes: &[u8]) -> Self {
-                use crate::types::get_byte;
-                use crate::types::get_longlong;
-                use crate::types::get_short;
-                use crate::types::get_string;
-                use crate::utils::compress_trailing_null_bytes;
-
-                let mut it = bytes.iter();
-
-                let header = get_byte(&mut it);
-                let protocol = get_byte(&mut it);
-                let name = get_string(&mut it);
-                let map = get_string(&mut it);
-                let folder = get_string(&mut it);
-                let game = get_string(&mut it);
-                let id = get_short(&mut it);
-                let players = get_byte(&mut it);
-                let max_players = get_byte(&mut it);
-                let bots = get_byte(&mut it);
-                let server_type = ServerType::from_byte(&get_byte(&mut it));
-                let environment = Platform::from_byte(&get_byte(&mut it));
-                let visibility = Visibility::from_byte(&get_byte(&mut it));
-                let vac = Vac::from_byte(&get_byte(&mut it));
-                let game_version = get_string(&mut it);
-
-                let extra_data_flag: Option<u8>;
-                if let Some(u) = it.next() {
-                    extra_data_flag = Some(*u);
-                } else {
-                    extra_data_flag = None;
+        /// Fields of an [`Info`] as serialized, named after its getters.
+        #[cfg(feature = "serde")]
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct InfoFields {
+            query_protocol: Protocol,
+            protocol: Byte,
+            name: String,
+            map: String,
+            folder: String,
+            game: String,
+            steam_app_id: Short,
+            player_count: Byte,
+            player_max: Byte,
+            bot_count: Byte,
+            server_type: ServerType,
+            platform: Platform,
+            visibility: Visibility,
+            vac: Vac,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            the_ship: Option<TheShipInfo>,
+            game_version: String,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            extra_data_flag: Option<Byte>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            port: Option<Short>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            steam_id: Option<LongLong>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            source_tv: Option<SourceTv>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            keywords: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            game_id: Option<LongLong>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            trailing_bytes: Option<Vec<Byte>>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            address: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            mod_info: Option<ModInfo>,
+        }
+
+        #[cfg(feature = "serde")]
+        impl From<Info> for InfoFields {
+            fn from(info: Info) -> Self {
+                Self {
+                    query_protocol: info.query_protocol(),
+                    source_tv: info.source_tv(),
+                    protocol: info.protocol,
+                    name: info.name,
+                    map: info.map,
+                    folder: info.folder,
+                    game: info.game,
+                    steam_app_id: info.id,
+                    player_count: info.players,
+                    player_max: info.max_players,
+                    bot_count: info.bots,
+                    server_type: info.server_type,
+                    platform: info.environment,
+                    visibility: info.visibility,
+                    vac: info.vac,
+                    the_ship: info.the_ship,
+                    game_version: info.game_version,
+                    extra_data_flag: info.extra_data_flag,
+                    port: info.port,
+                    steam_id: info.steam_id,
+                    keywords: info.keywords,
+                    game_id: info.game_id,
+                    trailing_bytes: info.trailing_bytes,
+                    address: info.address,
+                    mod_info: info.mod_info,
                 }
+            }
+        }
 
-                let port: Option<Short>;
-                if extra_data_flag.is_some() && (extra_data_flag.expect("data exists") & 0x80) != 0
-                {
-                    port = Some(get_short(&mut it));
-                } else {
-                    port = None;
+        /// Check the fields agree as in a response, so that the `Info` serializes with
+        /// [`Info::to_bytes`] to a payload that parses back to it.
+        #[cfg(feature = "serde")]
+        impl TryFrom<InfoFields> for Info {
+            type Error = String;
+
+            fn try_from(fields: InfoFields) -> Result<Self, Self::Error> {
+                let goldsource = fields.query_protocol == Protocol::GoldSource;
+                let extra_data = ExtraData::from_byte(fields.extra_data_flag.unwrap_or_default());
+                let present = [
+                    ("port", fields.port.is_some(), extra_data.has_port),
+                    (
+                        "steam_id",
+                        fields.steam_id.is_some(),
+                        extra_data.has_steam_id,
+                    ),
+                    (
+                        "source_tv",
+                        fields.source_tv.is_some(),
+                        extra_data.has_source_tv,
+                    ),
+                    (
+                        "keywords",
+                        fields.keywords.is_some(),
+                        extra_data.has_keywords,
+                    ),
+                    ("game_id", fields.game_id.is_some(), extra_data.has_game_id),
+                ];
+                for (field, present, declared) in present {
+                    if present != declared {
+                        return Err(format!(
+                            "{field} does not agree with the extra data flag {:?}",
+                            fields.extra_data_flag
+                        ));
+                    }
+                }
+                if goldsource && fields.extra_data_flag.is_some() {
+                    return Err("GoldSource response with an extra data flag".to_string());
+                }
+                if goldsource != fields.address.is_some() {
+                    return Err("address is sent in GoldSource responses only".to_string());
+                }
+                if !goldsource && fields.mod_info.is_some() {
+                    return Err("mod_info is sent in GoldSource responses only".to_string());
+                }
+                let the_ship = !goldsource && fields.steam_app_id == Info::THE_SHIP_APP_ID;
+                if the_ship != fields.the_ship.is_some() {
+                    return Err(format!(
+                        "the_ship is sent for the AppID {} only",
+                        Info::THE_SHIP_APP_ID
+                    ));
                 }
 
-                let steam_id: Option<LongLong>;
-                if extra_data_flag.is_some() && (extra_data_flag.expect("data exists") & 0x10) != 0
-                {
-                    steam_id = Some(get_longlong(&mut it));
-                } else {
-                    steam_id = None;
+                let header = match fields.query_protocol {
+                    Protocol::GoldSource => Info::GOLDSOURCE_HEADER,
+                    Protocol::Source => Info::HEADER,
+                };
+                let (spectator_port, spectator_name) = match fields.source_tv {
+                    Some(source_tv) => (Some(source_tv.port), Some(source_tv.name)),
+                    None => (None, None),
+                };
+                Ok(Self {
+                    header,
+                    protocol: fields.protocol,
+                    name: fields.name,
+                    map: fields.map,
+                    folder: fields.folder,
+                    game: fields.game,
+                    id: fields.steam_app_id,
+                    players: fields.player_count,
+                    max_players: fields.player_max,
+                    bots: fields.bot_count,
+                    server_type: fields.server_type,
+                    environment: fields.platform,
+                    visibility: fields.visibility,
+                    vac: fields.vac,
+                    the_ship: fields.the_ship,
+                    game_version: fields.game_version,
+                    extra_data_flag: fields.extra_data_flag,
+                    port: fields.port,
+                    steam_id: fields.steam_id,
+                    spectator_port,
+                    spectator_name,
+                    keywords: fields.keywords,
+                    game_id: fields.game_id,
+                    trailing_bytes: fields.trailing_bytes,
+                    address: fields.address,
+                    mod_info: fields.mod_info,
+                })
+            }
+        }
+
+        /// Half-Life mod information, sent in the obsolete GoldSource A2S_INFO response.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct ModInfo {
+            /// URL to the mod website.
+            pub link: String,
+            /// URL to download the mod.
+            pub download_link: String,
+            /// Version of the mod installed on the server.
+            pub version: Long,
+            /// Space (in bytes) the mod takes up.
+            pub size: Long,
+            /// Whether the mod is multiplayer only, rather than single and multiplayer.
+            pub multiplayer_only: bool,
+            /// Whether the mod uses its own DLL, rather than the Half-Life DLL.
+            pub custom_dll: bool,
+        }
+
+        /// SourceTV relay of a server.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct SourceTv {
+            /// Spectator port number for SourceTV.
+            pub port: Short,
+            /// Name of the spectator server for SourceTV.
+            pub name: String,
+        }
+
+        /// Game mode and settings sent by servers running The Ship, after the VAC byte.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct TheShipInfo {
+            /// Game mode:
+            /// 0 for Hunt
+            /// 1 for Elimination
+            /// 2 for Duel
+            /// 3 for Deathmatch
+            /// 4 for VIP Team
+            /// 5 for Team Elimination
+            pub mode: Byte,
+            /// Number of witnesses needed to have a player arrested.
+            pub witnesses: Byte,
+            /// Time (in seconds) before a player is arrested while being witnessed.
+            pub duration: Byte,
+        }
+
+        /// Borrowed view of an A2S_INFO response, whose strings borrow from the payload
+        /// rather than being copied into owned `String`s.
+        ///
+        /// Strings are only allocated when they must be converted, such as invalid UTF-8
+        /// decoded lossily, and the obsolete GoldSource mod information is always owned.
+        /// Convert to an [`Info`] with [`InfoRef::to_owned`] to keep it past the payload.
+        ///
+        /// ```
+        /// use valve_server_query::types::put_cstring;
+        /// use valve_server_query::InfoRef;
+        ///
+        /// let mut payload = vec![0x49, 0x11];
+        /// for field in ["My Server", "de_dust2", "cstrike", "Counter-Strike"] {
+        ///     put_cstring(&mut payload, field);
+        /// }
+        /// payload.extend([0x0A, 0x00, 0x05, 0x10, 0x00, b'd', b'l', 0x00, 0x01]);
+        /// put_cstring(&mut payload, "1.0.0.0");
+        ///
+        /// let info = InfoRef::from_bytes(&payload).unwrap();
+        /// assert_eq!(info.map, "de_dust2");
+        /// assert_eq!(info.player_count, 5);
+        /// assert_eq!(info.to_owned().name(), "My Server");
+        /// ```
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        pub struct InfoRef<'a> {
+            /// Response header. 'I' (0x49), or 'm' (0x6D) for the obsolete GoldSource response.
+            pub header: Byte,
+            /// Protocol version used by the server.
+            pub protocol: Byte,
+            /// Name of the server.
+            pub name: Cow<'a, str>,
+            /// Map the server has currently loaded.
+            pub map: Cow<'a, str>,
+            /// Name of the folder containing the game files.
+            pub folder: Cow<'a, str>,
+            /// Full name of the game.
+            pub game: Cow<'a, str>,
+            /// Steam Application ID of game.
+            pub steam_app_id: Short,
+            /// Number of players on the server.
+            pub player_count: Byte,
+            /// Maximum number of players the server reports it can hold.
+            pub player_max: Byte,
+            /// Number of bots on the server.
+            pub bot_count: Byte,
+            /// Indicates the type of server.
+            pub server_type: ServerType,
+            /// Indicates the operating system of the server.
+            pub platform: Platform,
+            /// Indicates whether the server requires a password.
+            pub visibility: Visibility,
+            /// Specifies whether the server uses VAC.
+            pub vac: Vac,
+            /// Game mode and settings of The Ship (AppID 2400) servers.
+            pub the_ship: Option<TheShipInfo>,
+            /// Version of the game installed on the server.
+            pub game_version: Cow<'a, str>,
+            /// Flag for Extra Features
+            pub extra_data_flag: Option<Byte>,
+            /// The server's game port number.
+            pub port: Option<Short>,
+            /// Server's SteamID.
+            pub steam_id: Option<LongLong>,
+            /// Spectator port number for SourceTV.
+            pub spectator_port: Option<Short>,
+            /// Name of the spectator server for SourceTV.
+            pub spectator_name: Option<Cow<'a, str>>,
+            /// Tags that describe the game according to the server.
+            pub keywords: Option<Cow<'a, str>>,
+            /// The server's 64-bit GameID.
+            pub game_id: Option<LongLong>,
+            /// Bytes following the fields this crate knows, or `None` if nothing followed.
+            pub trailing_bytes: Option<&'a [u8]>,
+            /// IP address and port of the server (obsolete GoldSource response only).
+            pub address: Option<Cow<'a, str>>,
+            /// Information about the mod the server runs (obsolete GoldSource response only).
+            pub mod_info: Option<ModInfo>,
+        }
+
+        impl<'a> InfoRef<'a> {
+            pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, QueryError> {
+                Self::from_reader(PacketReader::new(bytes))
+            }
+
+            /// Parse a response from a reader positioned at its header, decoding strings with
+            /// the encoding of the reader. The rest of the reader is kept as trailing bytes.
+            pub fn from_reader(mut reader: PacketReader<'a>) -> Result<Self, QueryError> {
+                let header = reader.read_u8()?;
+                if header == Info::GOLDSOURCE_HEADER {
+                    return Self::from_goldsource_reader(reader);
+                }
+                if header != Info::HEADER {
+                    return Err(QueryError::UnexpectedHeader {
+                        expected: Info::HEADER,
+                        found: header,
+                    });
                 }
+                let protocol = reader.read_u8()?;
+                let name = reader.read_cstr()?;
+                let map = reader.read_cstr()?;
+                let folder = reader.read_cstr()?;
+                let game = reader.read_cstr()?;
+                let id = reader.read_i16()?;
+                let players = reader.read_u8()?;
+                let max_players = reader.read_u8()?;
+                let bots = reader.read_u8()?;
+                let server_type = read_byte_as(&mut reader, ServerType::from_byte)?;
+                let environment = read_byte_as(&mut reader, Platform::from_byte)?;
+                let visibility = read_byte_as(&mut reader, Visibility::from_byte)?;
+                let vac = read_byte_as(&mut reader, Vac::from_byte)?;
+                let the_ship = if id == Info::THE_SHIP_APP_ID {
+                    Some(TheShipInfo {
+                        mode: reader.read_u8()?,
+                        witnesses: reader.read_u8()?,
+                        duration: reader.read_u8()?,
+                    })
+                } else {
+                    None
+                };
+                let game_version = reader.read_cstr()?;
 
-                let spectator_port: Option<Short>;
-                let spectator_name: Option<String>;
-                if extra_data_flag.is_some() && (extra_data_flag.expect("data exists") & 0x40) != 0
-                {
-                    spectator_port = Some(get_short(&mut it));
-                    spectator_name = Some(get_string(&mut it));
+                let extra_data_flag = reader.read_u8().ok();
+
+                let extra_data = extra_data_flag
+                    .map(ExtraData::from_byte)
+                    .unwrap_or_default();
+
+                let port: Option<Short> = if extra_data.has_port {
+                    Some(reader.read_i16()?)
                 } else {
-                    spectator_port = None;
-                    spectator_name = None;
-                }
+                    None
+                };
 
-                let keywords: Option<String>;
-                if extra_data_flag.is_some() && (extra_data_flag.expect("data exists") & 0x20) != 0
-                {
-                    keywords = Some(get_string(&mut it));
+                let steam_id: Option<LongLong> = if extra_data.has_steam_id {
+                    Some(reader.read_u64()?)
                 } else {
-                    keywords = None;
-                }
+                    None
+                };
 
-                let game_id: Option<LongLong>;
-                if extra_data_flag.is_some() && (extra_data_flag.expect("data exists") & 0x01) != 0
-                {
-                    game_id = Some(get_longlong(&mut it));
+                let spectator_port: Option<Short>;
+                let spectator_name: Option<Cow<'a, str>>;
+                if extra_data.has_source_tv {
+                    spectator_port = Some(reader.read_i16()?);
+                    spectator_name = Some(reader.read_cstr()?);
                 } else {
-                    game_id = None;
+                    spectator_port = None;
+                    spectator_name = None;
                 }
 
-                // These are hanging bytes that were not parsed
-                let trailing_bytes: Option<Vec<u8>> = if it.len() > 0 {
-                    // Remove trailing null bytes (and leave one if there are any)
-                    let mut min_bytes: Vec<u8> = it.into_iter().map(|x| *x).collect();
-                    compress_trailing_null_bytes(&mut min_bytes);
+                let keywords: Option<Cow<'a, str>> = if extra_data.has_keywords {
+                    Some(reader.read_cstr()?)
+                } else {
+                    None
+                };
 
-                    // Just a [0]
-                    if min_bytes.len() == 1 && *min_bytes.last().expect("last byte exists") == 0 {
-                        None
-                    } else {
-                        Some(min_bytes.into_iter().collect::<Vec<u8>>())
-                    }
+                let game_id: Option<LongLong> = if extra_data.has_game_id {
+                    Some(reader.read_u64()?)
                 } else {
                     None
                 };
 
-                Self {
+                let rest = reader.rest();
+                let trailing_bytes = (!rest.is_empty()).then_some(rest);
+
+                Ok(Self {
                     header,
                     game_id,
                     trailing_bytes,
@@ -402,22 +2056,333 @@ pub mod models {
                     map,
                     folder,
                     game,
-                    id,
-                    players,
-                    max_players,
-                    bots,
+                    steam_app_id: id,
+                    player_count: players,
+                    player_max: max_players,
+                    bot_count: bots,
                     server_type,
-                    environment,
+                    platform: environment,
                     visibility,
                     vac,
+                    the_ship,
                     game_version,
                     port,
+                    address: None,
+                    mod_info: None,
+                })
+            }
+
+            /// Parse the obsolete GoldSource response, following its 'm' header.
+            ///
+            /// Ref: <https://developer.valvesoftware.com/wiki/Server_queries#Obsolete_GoldSource_Response>
+            fn from_goldsource_reader(mut reader: PacketReader<'a>) -> Result<Self, QueryError> {
+                let address = reader.read_cstr()?;
+                let name = reader.read_cstr()?;
+                let map = reader.read_cstr()?;
+                let folder = reader.read_cstr()?;
+                let game = reader.read_cstr()?;
+                let players = reader.read_u8()?;
+                let max_players = reader.read_u8()?;
+                let protocol = reader.read_u8()?;
+                let server_type = read_byte_as(&mut reader, ServerType::from_byte)?;
+                let environment = read_byte_as(&mut reader, Platform::from_byte)?;
+                let visibility = read_byte_as(&mut reader, Visibility::from_byte)?;
+
+                let is_mod = reader.read_u8()? == 0x01;
+                let mod_info = if is_mod {
+                    let link = reader.read_cstring()?;
+                    let download_link = reader.read_cstring()?;
+                    let _ = reader.read_u8()?; // Null Byte
+                    let version = reader.read_i32()?;
+                    let size = reader.read_i32()?;
+                    let multiplayer_only = reader.read_u8()? == 0x01;
+                    let custom_dll = reader.read_u8()? == 0x01;
+                    Some(ModInfo {
+                        link,
+                        download_link,
+                        version,
+                        size,
+                        multiplayer_only,
+                        custom_dll,
+                    })
+                } else {
+                    None
+                };
+
+                let vac = read_byte_as(&mut reader, Vac::from_byte)?;
+                let bots = reader.read_u8()?;
+
+                let rest = reader.rest();
+                let trailing_bytes = (!rest.is_empty()).then_some(rest);
+
+                Ok(Self {
+                    header: Info::GOLDSOURCE_HEADER,
+                    protocol,
+                    name,
+                    map,
+                    folder,
+                    game,
+                    // Not sent by GoldSource
+                    steam_app_id: 0,
+                    player_count: players,
+                    player_max: max_players,
+                    bot_count: bots,
+                    server_type,
+                    platform: environment,
+                    visibility,
+                    vac,
+                    // Not sent by GoldSource
+                    the_ship: None,
+                    game_version: Cow::Borrowed(""),
+                    extra_data_flag: None,
+                    port: None,
+                    steam_id: None,
+                    spectator_port: None,
+                    spectator_name: None,
+                    keywords: None,
+                    game_id: None,
+                    trailing_bytes,
+                    address: Some(address),
+                    mod_info,
+                })
+            }
+
+            /// Copy the response into an owned [`Info`].
+            pub fn to_owned(&self) -> Info {
+                let owned = |string: &Option<Cow<str>>| string.as_deref().map(String::from);
+                Info {
+                    header: self.header,
+                    protocol: self.protocol,
+                    name: self.name.to_string(),
+                    map: self.map.to_string(),
+                    folder: self.folder.to_string(),
+                    game: self.game.to_string(),
+                    id: self.steam_app_id,
+                    players: self.player_count,
+                    max_players: self.player_max,
+                    bots: self.bot_count,
+                    server_type: self.server_type.clone(),
+                    environment: self.platform.clone(),
+                    visibility: self.visibility.clone(),
+                    vac: self.vac.clone(),
+                    the_ship: self.the_ship.clone(),
+                    game_version: self.game_version.to_string(),
+                    extra_data_flag: self.extra_data_flag,
+                    port: self.port,
+                    steam_id: self.steam_id,
+                    spectator_port: self.spectator_port,
+                    spectator_name: owned(&self.spectator_name),
+                    keywords: owned(&self.keywords),
+                    game_id: self.game_id,
+                    trailing_bytes: self.trailing_bytes.map(<[u8]>::to_vec),
+                    address: owned(&self.address),
+                    mod_info: self.mod_info.clone(),
+                }
+            }
+        }
+
+        impl From<InfoRef<'_>> for Info {
+            fn from(info: InfoRef<'_>) -> Self {
+                info.to_owned()
+            }
+        }
+
+        impl Info {
+            /// Response header for A2S_INFO, 'I'.
+            pub const HEADER: Byte = ResponseKind::Info.as_byte();
+            /// Response header for the obsolete GoldSource A2S_INFO response, 'm'.
+            pub const GOLDSOURCE_HEADER: Byte = ResponseKind::GoldSourceInfo.as_byte();
+            /// Steam Application ID of The Ship, whose response carries extra fields.
+            pub const THE_SHIP_APP_ID: Short = 2400;
+
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, QueryError> {
+                Self::from_reader(PacketReader::new(bytes))
+            }
+
+            /// Parse a response like [`Info::from_bytes`], also returning the number of bytes
+            /// parsed, which excludes the [trailing bytes](Info::trailing_bytes).
+            ///
+            /// ```
+            /// use valve_server_query::types::put_cstring;
+            /// use valve_server_query::Info;
+            ///
+            /// let mut bytes = vec![0x49, 0x11];
+            /// for field in ["My Server", "de_dust2", "cstrike", "Counter-Strike"] {
+            ///     put_cstring(&mut bytes, field);
+            /// }
+            /// bytes.extend([0x0A, 0x00, 0x05, 0x10, 0x00, b'd', b'l', 0x00, 0x01]);
+            /// put_cstring(&mut bytes, "1.0.0.0");
+            /// // Extra Data Flag, declaring no fields
+            /// bytes.push(0x00);
+            /// let length = bytes.len();
+            /// bytes.extend(b"next structure");
+            ///
+            /// let (_info, consumed) = Info::parse(&bytes).unwrap();
+            /// assert_eq!(consumed, length);
+            /// ```
+            pub fn parse(bytes: &[u8]) -> Result<(Self, usize), QueryError> {
+                let info = Self::from_bytes(bytes)?;
+                let consumed = bytes.len() - info.trailing_bytes_len();
+                Ok((info, consumed))
+            }
+
+            /// Compare with a later response, such as from the next poll of the server.
+            ///
+            /// ```
+            /// use valve_server_query::Info;
+            ///
+            /// let before = Info::builder().map("de_dust2").players(12).build();
+            /// let after = Info::builder()
+            ///     .map("de_nuke")
+            ///     .players(17)
+            ///     .keywords("secure")
+            ///     .build();
+            ///
+            /// let delta = before.diff(&after);
+            /// assert_eq!(
+            ///     delta.to_string(),
+            ///     "map: de_dust2 → de_nuke, players: 12 → 17, keywords: (none) → secure"
+            /// );
+            /// assert!(after.diff(&after).is_empty());
+            /// ```
+            pub fn diff(&self, other: &Info) -> InfoDelta {
+                InfoDelta {
+                    name: change(&self.name, &other.name),
+                    map: change(&self.map, &other.map),
+                    players: change(&self.players, &other.players),
+                    max_players: change(&self.max_players, &other.max_players),
+                    bots: change(&self.bots, &other.bots),
+                    visibility: change(&self.visibility, &other.visibility),
+                    vac: change(&self.vac, &other.vac),
+                    game_version: change(&self.game_version, &other.game_version),
+                    keywords: change(&self.keywords, &other.keywords),
+                }
+            }
+
+            /// Build an `Info` directly, such as a fixture for tests, see [`InfoBuilder`].
+            pub fn builder() -> InfoBuilder {
+                InfoBuilder::default()
+            }
+
+            /// Serialize the response payload, the inverse of [`Info::from_bytes`].
+            ///
+            /// The payload starts at the header byte, in the obsolete GoldSource layout if the
+            /// header is [`Info::GOLDSOURCE_HEADER`]. The Extra Data Flag is written as stored,
+            /// followed by the fields present, then the [trailing bytes](Info::trailing_bytes).
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = vec![self.header];
+                if self.header == Self::GOLDSOURCE_HEADER {
+                    self.put_goldsource_fields(&mut bytes);
+                } else {
+                    self.put_fields(&mut bytes);
+                }
+                bytes.extend(self.trailing_bytes.as_deref().unwrap_or_default());
+                bytes
+            }
+
+            fn put_fields(&self, bytes: &mut Vec<u8>) {
+                put_byte(bytes, self.protocol);
+                for string in [&self.name, &self.map, &self.folder, &self.game] {
+                    put_cstring(bytes, string);
+                }
+                put_short(bytes, self.id);
+                for byte in [
+                    self.players,
+                    self.max_players,
+                    self.bots,
+                    self.server_type.to_byte(),
+                    self.environment.as_byte(),
+                    self.visibility.to_byte(),
+                    self.vac.to_byte(),
+                ] {
+                    put_byte(bytes, byte);
+                }
+                if let Some(the_ship) = &self.the_ship {
+                    for byte in [the_ship.mode, the_ship.witnesses, the_ship.duration] {
+                        put_byte(bytes, byte);
+                    }
+                }
+                put_cstring(bytes, &self.game_version);
+
+                let Some(flag) = self.extra_data_flag else {
+                    return;
+                };
+                put_byte(bytes, flag);
+                if let Some(port) = self.port {
+                    put_short(bytes, port);
+                }
+                if let Some(steam_id) = self.steam_id {
+                    put_longlong(bytes, steam_id);
+                }
+                if let (Some(port), Some(name)) = (self.spectator_port, &self.spectator_name) {
+                    put_short(bytes, port);
+                    put_cstring(bytes, name);
+                }
+                if let Some(keywords) = &self.keywords {
+                    put_cstring(bytes, keywords);
+                }
+                if let Some(game_id) = self.game_id {
+                    put_longlong(bytes, game_id);
+                }
+            }
+
+            fn put_goldsource_fields(&self, bytes: &mut Vec<u8>) {
+                put_cstring(bytes, self.address.as_deref().unwrap_or_default());
+                for string in [&self.name, &self.map, &self.folder, &self.game] {
+                    put_cstring(bytes, string);
+                }
+                for byte in [
+                    self.players,
+                    self.max_players,
+                    self.protocol,
+                    // Uppercase in the obsolete GoldSource response
+                    self.server_type.to_byte().to_ascii_uppercase(),
+                    self.environment.as_byte().to_ascii_uppercase(),
+                    self.visibility.to_byte(),
+                    self.mod_info.is_some().into(),
+                ] {
+                    put_byte(bytes, byte);
                 }
+                if let Some(mod_info) = &self.mod_info {
+                    put_cstring(bytes, &mod_info.link);
+                    put_cstring(bytes, &mod_info.download_link);
+                    put_byte(bytes, 0x00);
+                    put_long(bytes, mod_info.version);
+                    put_long(bytes, mod_info.size);
+                    put_byte(bytes, mod_info.multiplayer_only.into());
+                    put_byte(bytes, mod_info.custom_dll.into());
+                }
+                put_byte(bytes, self.vac.to_byte());
+                put_byte(bytes, self.bots);
+            }
+
+            /// Parse a response without copying its strings, see [`InfoRef`].
+            #[deprecated(since = "0.4.10", note = "use `InfoRef::from_bytes`")]
+            pub fn parse_ref(bytes: &[u8]) -> Result<InfoRef<'_>, QueryError> {
+                InfoRef::from_bytes(bytes)
+            }
+
+            /// Parse a response from a reader positioned at its header, decoding strings with
+            /// the encoding of the reader. The rest of the reader is kept as trailing bytes.
+            pub fn from_reader(reader: PacketReader) -> Result<Self, QueryError> {
+                InfoRef::from_reader(reader).map(|info| info.to_owned())
             }
         }
 
         /// Getters (Immutable)
         impl Info {
+            /// Response header. 'I' (0x49), or 'm' (0x6D) for the obsolete GoldSource response.
+            pub fn header(&self) -> Byte {
+                self.header
+            }
+            /// Protocol version used by the server.
+            pub fn protocol(&self) -> Byte {
+                self.protocol
+            }
+            /// Variant of the A2S_INFO response the server replied with.
+            pub fn query_protocol(&self) -> Protocol {
+                Protocol::from_header(self.header).expect("header is validated when parsing")
+            }
             /// Name of the server.
             pub fn name(&self) -> &str {
                 &self.name
@@ -435,6 +2400,41 @@ pub mod models {
             pub fn keywords(&self) -> &Option<String> {
                 &self.keywords
             }
+            /// [Keywords](Info::keywords) split on commas, trimmed of whitespace, skipping empty
+            /// ones.
+            ///
+            /// ```
+            /// # use valve_server_query::types::put_cstring;
+            /// # use valve_server_query::Info;
+            /// # let mut bytes = vec![0x49, 0x11];
+            /// # for field in ["My Server", "cp_badlands", "tf", "Team Fortress"] {
+            /// #     put_cstring(&mut bytes, field);
+            /// # }
+            /// # bytes.extend([0xB8, 0x01, 0x05, 0x18, 0x00, b'd', b'l', 0x00, 0x01]);
+            /// # put_cstring(&mut bytes, "1.0.0.0");
+            /// # bytes.push(0x20);
+            /// # put_cstring(&mut bytes, "alltalk, nocrits,");
+            /// # let info = Info::from_bytes(&bytes).unwrap();
+            /// // Keywords of "alltalk, nocrits,"
+            /// assert_eq!(info.keywords_list(), ["alltalk", "nocrits"]);
+            /// assert!(info.has_keyword("NoCrits"));
+            /// ```
+            pub fn keywords_list(&self) -> Vec<&str> {
+                self.keywords
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect()
+            }
+            /// Whether `keyword` is one of the [keywords](Info::keywords_list), ignoring ASCII
+            /// case.
+            pub fn has_keyword(&self, keyword: &str) -> bool {
+                self.keywords_list()
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(keyword.trim()))
+            }
 
             /// Full name of the game.
             pub fn game(&self) -> &str {
@@ -446,14 +2446,54 @@ pub mod models {
             pub fn game_id(&self) -> &Option<LongLong> {
                 &self.game_id
             }
+            /// Bytes following the fields this crate knows, such as fields added by a later
+            /// protocol revision, left uninterpreted.
+            ///
+            /// The bytes are kept exactly as received, null bytes included, and `None` is
+            /// returned if nothing followed.
+            pub fn trailing_bytes(&self) -> Option<&[u8]> {
+                self.trailing_bytes.as_deref()
+            }
+            /// Number of [trailing bytes](Info::trailing_bytes), 0 if there are none.
+            pub fn trailing_bytes_len(&self) -> usize {
+                self.trailing_bytes.as_ref().map_or(0, Vec::len)
+            }
             /// Version of the game installed on the server.
             pub fn game_version(&self) -> &str {
                 &self.game_version
             }
-            /// Steam Application ID of game
+            /// Steam Application ID of game, as the raw 16-bit field.
+            ///
+            /// AppIDs above 32767 read as negative, and those above 65535 are truncated. Prefer
+            /// [`Info::app_id`].
             pub fn steam_app_id(&self) -> &Short {
                 &self.id
             }
+            /// Steam Application ID of the game, from the low 24 bits of the
+            /// [GameID](Info::game_id) if present, which is not truncated to 16 bits like
+            /// [`Info::steam_app_id`].
+            pub fn app_id(&self) -> u32 {
+                match self.game_id {
+                    Some(game_id) => (game_id & 0xFF_FFFF) as u32,
+                    None => self.id as u16 as u32,
+                }
+            }
+            /// The [AppID](Info::app_id) as an [`AppId`], which knows the name of well-known
+            /// games.
+            pub fn app(&self) -> AppId {
+                AppId(self.app_id())
+            }
+            /// Bits of the [GameID](Info::game_id) above its 24-bit AppID, shifted down: the
+            /// type of game in the low 8 bits, then the ID of a mod in the upper 32 bits.
+            ///
+            /// `None` if the server sent no GameID, and 0 for a plain Steam game.
+            pub fn game_id_flags(&self) -> Option<LongLong> {
+                self.game_id.map(|game_id| game_id >> 24)
+            }
+            /// Display name of the game if it is well known, see [`AppId::name`].
+            pub fn game_name(&self) -> Option<&'static str> {
+                self.app().name()
+            }
             /// Server's SteamID.
             pub fn steam_id(&self) -> &Option<LongLong> {
                 &self.steam_id
@@ -471,6 +2511,55 @@ pub mod models {
             pub fn bot_count(&self) -> &Byte {
                 &self.bots
             }
+            /// Fraction of the player slots in use, between 0 and 1.
+            ///
+            /// `None` if the server reports no player slots. A player count above the maximum,
+            /// as reported by some misconfigured servers, is clamped to 1.
+            pub fn fill_ratio(&self) -> Option<f32> {
+                if self.max_players == 0 {
+                    return None;
+                }
+                Some((self.players as f32 / self.max_players as f32).min(1.0))
+            }
+            /// Number of human players, the player count less the bots it includes.
+            pub fn human_count(&self) -> Byte {
+                self.players.saturating_sub(self.bots)
+            }
+            /// Number of player slots left, 0 if the player count reaches or exceeds the
+            /// maximum.
+            pub fn free_slots(&self) -> Byte {
+                self.max_players.saturating_sub(self.players)
+            }
+            /// Number of player slots left for humans, counting slots taken by bots as free,
+            /// since servers usually kick a bot to make room for a joining player.
+            pub fn free_slots_excluding_bots(&self) -> Byte {
+                self.max_players.saturating_sub(self.human_count())
+            }
+            /// Whether every player slot is taken, by humans or bots.
+            pub fn is_full(&self) -> bool {
+                self.free_slots() == 0
+            }
+            /// Whether every player slot is taken by humans, see
+            /// [`Info::free_slots_excluding_bots`].
+            pub fn is_full_excluding_bots(&self) -> bool {
+                self.free_slots_excluding_bots() == 0
+            }
+            /// Whether there are no players at all, humans or bots.
+            pub fn is_empty(&self) -> bool {
+                self.players == 0
+            }
+            /// Whether there are no human players, though bots may be playing.
+            pub fn is_empty_excluding_bots(&self) -> bool {
+                self.human_count() == 0
+            }
+            /// Whether the server requires a password, see [`Info::visibility`].
+            pub fn has_password(&self) -> bool {
+                self.visibility == Visibility::Private
+            }
+            /// Whether the server is secured by VAC, see [`Info::vac`].
+            pub fn is_vac_secured(&self) -> bool {
+                self.vac == Vac::Secured
+            }
 
             /// Indicates the type of server
             pub fn server_type(&self) -> &ServerType {
@@ -489,11 +2578,40 @@ pub mod models {
             pub fn vac(&self) -> &Vac {
                 &self.vac
             }
+            /// Game mode and settings, only sent by servers running The Ship.
+            pub fn the_ship(&self) -> Option<&TheShipInfo> {
+                self.the_ship.as_ref()
+            }
 
             /// The server's game port number
+            ///
+            /// Like every short in the protocol, it is sent little-endian, but as a signed
+            /// short: ports above 32767 come out negative. See [`Info::game_port`].
             pub fn port(&self) -> &Option<Short> {
                 &self.port
             }
+            /// The game port the server advertises in its extra data, as an unsigned port number.
+            ///
+            /// May differ from the queried port, such as for servers answering queries on a
+            /// separate port.
+            pub fn game_port(&self) -> Option<u16> {
+                self.port.map(|port| port as u16)
+            }
+            /// Canonical game address of the server, for telling apart servers reached through
+            /// different query addresses.
+            ///
+            /// The address reported by a GoldSource server if it parses, otherwise the IP
+            /// address queried with the advertised [game port](Info::game_port), or `queried`
+            /// itself if the server advertises none.
+            pub fn game_address(&self, queried: SocketAddr) -> SocketAddr {
+                if let Some(address) = self.address.as_deref().and_then(|a| a.parse().ok()) {
+                    return address;
+                }
+                match self.game_port() {
+                    Some(port) => SocketAddr::new(queried.ip(), port),
+                    None => queried,
+                }
+            }
             /// Name of the spectator server for SourceTV.
             pub fn spectator_name(&self) -> &Option<String> {
                 &self.spectator_name
@@ -502,505 +2620,6756 @@ pub mod models {
             pub fn spectator_port(&self) -> &Option<Short> {
                 &self.spectator_port
             }
+            /// SourceTV relay of the server, if both its port and name were sent.
+            pub fn source_tv(&self) -> Option<SourceTv> {
+                Some(SourceTv {
+                    port: self.spectator_port?,
+                    name: self.spectator_name.clone()?,
+                })
+            }
+            /// IP address and port of the server, only sent in the obsolete GoldSource response.
+            pub fn address(&self) -> Option<&str> {
+                self.address.as_deref()
+            }
+            /// Mod information, only sent in the obsolete GoldSource response when the server
+            /// runs a Half-Life mod.
+            pub fn mod_info(&self) -> Option<&ModInfo> {
+                self.mod_info.as_ref()
+            }
+            /// Optional fields declared by the Extra Data Flag, or `None` if the server sent no
+            /// flag.
+            pub fn extra_data(&self) -> Option<ExtraData> {
+                self.extra_data_flag.map(ExtraData::from_byte)
+            }
+            /// Raw Extra Data Flag, or `None` if the server sent no flag.
+            pub fn extra_data_flag(&self) -> Option<Byte> {
+                self.extra_data_flag
+            }
+            /// Whether the server declared its [game port](Info::port). Parsing fails if a
+            /// declared field is missing, so a field is `None` only when it was not declared.
+            pub fn has_port_field(&self) -> bool {
+                self.extra_data().is_some_and(|edf| edf.has_port)
+            }
+            /// Whether the server declared its [SteamID](Info::steam_id).
+            pub fn has_steam_id_field(&self) -> bool {
+                self.extra_data().is_some_and(|edf| edf.has_steam_id)
+            }
+            /// Whether the server declared its [SourceTV](Info::source_tv) port and name.
+            pub fn has_source_tv_field(&self) -> bool {
+                self.extra_data().is_some_and(|edf| edf.has_source_tv)
+            }
+            /// Whether the server declared its [keywords](Info::keywords).
+            pub fn has_keywords_field(&self) -> bool {
+                self.extra_data().is_some_and(|edf| edf.has_keywords)
+            }
+            /// Whether the server declared its [GameID](Info::game_id).
+            pub fn has_game_id_field(&self) -> bool {
+                self.extra_data().is_some_and(|edf| edf.has_game_id)
+            }
         }
 
-        #[derive(Debug, Eq, PartialEq, Clone)]
-        pub enum ServerType {
-            Dedicated,
-            NonDedicated,
-            SourceTvRelay,
+        /// Variant of the A2S_INFO response a server replied with.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+        pub enum Protocol {
+            /// Obsolete GoldSource response, with header 'm' (0x6D).
+            GoldSource,
+            /// Source response, with header 'I' (0x49). Also sent by up-to-date GoldSource
+            /// servers.
+            Source,
+        }
+
+        impl Protocol {
+            /// Protocol of an A2S_INFO response with the given header byte.
+            pub fn from_header(header: Byte) -> Option<Self> {
+                match ResponseKind::try_from_byte(header)? {
+                    ResponseKind::GoldSourceInfo => Some(Self::GoldSource),
+                    ResponseKind::Info => Some(Self::Source),
+                    _ => None,
+                }
+            }
+        }
+
+        /// Optional fields a server declares in the Extra Data Flag (EDF) of an A2S_INFO response.
+        #[derive(Debug, Default, Eq, PartialEq, Hash, Clone, Copy)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct ExtraData {
+            /// The server's game port is present.
+            pub has_port: bool,
+            /// The server's SteamID is present.
+            pub has_steam_id: bool,
+            /// The SourceTV port and name are present.
+            pub has_source_tv: bool,
+            /// The keywords are present.
+            pub has_keywords: bool,
+            /// The server's 64-bit GameID is present.
+            pub has_game_id: bool,
+        }
+
+        impl ExtraData {
+            pub const PORT: Byte = 0x80;
+            pub const STEAM_ID: Byte = 0x10;
+            pub const SOURCE_TV: Byte = 0x40;
+            pub const KEYWORDS: Byte = 0x20;
+            pub const GAME_ID: Byte = 0x01;
+
+            pub fn from_byte(flag: Byte) -> Self {
+                Self {
+                    has_port: flag & Self::PORT != 0,
+                    has_steam_id: flag & Self::STEAM_ID != 0,
+                    has_source_tv: flag & Self::SOURCE_TV != 0,
+                    has_keywords: flag & Self::KEYWORDS != 0,
+                    has_game_id: flag & Self::GAME_ID != 0,
+                }
+            }
+        }
+
+        /// Fields that changed between two responses, see [`Info::diff`], each with the old
+        /// value then the new one.
+        ///
+        /// Displays as the list of changes, such as `map: de_dust2 → de_nuke, players: 12 → 17`,
+        /// or nothing if no field changed. Missing keywords display as `(none)`.
+        #[derive(Debug, Default, Eq, PartialEq, Clone)]
+        pub struct InfoDelta {
+            pub name: Option<(String, String)>,
+            pub map: Option<(String, String)>,
+            pub players: Option<(Byte, Byte)>,
+            pub max_players: Option<(Byte, Byte)>,
+            pub bots: Option<(Byte, Byte)>,
+            pub visibility: Option<(Visibility, Visibility)>,
+            pub vac: Option<(Vac, Vac)>,
+            pub game_version: Option<(String, String)>,
+            pub keywords: Option<(Option<String>, Option<String>)>,
+        }
+
+        impl InfoDelta {
+            /// Whether no field changed.
+            pub fn is_empty(&self) -> bool {
+                *self == Self::default()
+            }
+        }
+
+        impl fmt::Display for InfoDelta {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fn show<T: fmt::Display>((old, new): &(T, T)) -> String {
+                    format!("{old} → {new}")
+                }
+                let keywords = self.keywords.as_ref().map(|(old, new)| {
+                    (
+                        old.as_deref().unwrap_or("(none)"),
+                        new.as_deref().unwrap_or("(none)"),
+                    )
+                });
+
+                let changes = [
+                    ("name", self.name.as_ref().map(show)),
+                    ("map", self.map.as_ref().map(show)),
+                    ("players", self.players.as_ref().map(show)),
+                    ("max_players", self.max_players.as_ref().map(show)),
+                    ("bots", self.bots.as_ref().map(show)),
+                    ("visibility", self.visibility.as_ref().map(show)),
+                    ("vac", self.vac.as_ref().map(show)),
+                    ("version", self.game_version.as_ref().map(show)),
+                    ("keywords", keywords.as_ref().map(show)),
+                ];
+                let mut first = true;
+                for (field, change) in changes {
+                    let Some(change) = change else {
+                        continue;
+                    };
+                    if !first {
+                        write!(f, ", ")?;
+                    }
+                    first = false;
+                    write!(f, "{field}: {change}")?;
+                }
+                Ok(())
+            }
+        }
+
+        /// The old and new value if they differ.
+        fn change<T: PartialEq + Clone>(old: &T, new: &T) -> Option<(T, T)> {
+            (old != new).then(|| (old.clone(), new.clone()))
+        }
+
+        /// Read a byte with `from_byte`, given the byte and its position.
+        fn read_byte_as<T>(
+            reader: &mut PacketReader,
+            from_byte: fn(Byte, usize) -> Result<T, QueryError>,
+        ) -> Result<T, QueryError> {
+            let position = reader.position();
+            from_byte(reader.read_u8()?, position)
+        }
+
+        /// Builder of an [`Info`] in the Source layout, such as a fixture for tests.
+        ///
+        /// Fields not set are empty or zero, with a dedicated Linux server that is public and
+        /// not secured by VAC. The Extra Data Flag declares the optional fields set, and is
+        /// left out if none is.
+        ///
+        /// ```
+        /// use valve_server_query::Info;
+        ///
+        /// let info = Info::builder()
+        ///     .name("test")
+        ///     .map("de_dust2")
+        ///     .players(10)
+        ///     .max_players(24)
+        ///     .port(27015)
+        ///     .build();
+        ///
+        /// assert_eq!(info.map(), "de_dust2");
+        /// assert_eq!(info.free_slots(), 14);
+        /// assert_eq!(Info::from_bytes(&info.to_bytes()).unwrap(), info);
+        /// ```
+        #[derive(Debug, Clone)]
+        pub struct InfoBuilder {
+            info: Info,
+        }
+
+        impl Default for InfoBuilder {
+            fn default() -> Self {
+                Self {
+                    info: Info {
+                        header: Info::HEADER,
+                        protocol: 0x11,
+                        name: String::new(),
+                        map: String::new(),
+                        folder: String::new(),
+                        game: String::new(),
+                        id: 0,
+                        players: 0,
+                        max_players: 0,
+                        bots: 0,
+                        server_type: ServerType::Dedicated,
+                        environment: Platform::Linux,
+                        visibility: Visibility::Public,
+                        vac: Vac::Unsecured,
+                        the_ship: None,
+                        game_version: String::new(),
+                        extra_data_flag: None,
+                        port: None,
+                        steam_id: None,
+                        spectator_port: None,
+                        spectator_name: None,
+                        keywords: None,
+                        game_id: None,
+                        trailing_bytes: None,
+                        address: None,
+                        mod_info: None,
+                    },
+                }
+            }
+        }
+
+        impl InfoBuilder {
+            pub fn protocol(mut self, protocol: Byte) -> Self {
+                self.info.protocol = protocol;
+                self
+            }
+            pub fn name(mut self, name: &str) -> Self {
+                self.info.name = name.to_string();
+                self
+            }
+            pub fn map(mut self, map: &str) -> Self {
+                self.info.map = map.to_string();
+                self
+            }
+            pub fn folder(mut self, folder: &str) -> Self {
+                self.info.folder = folder.to_string();
+                self
+            }
+            pub fn game(mut self, game: &str) -> Self {
+                self.info.game = game.to_string();
+                self
+            }
+            /// The 16-bit AppID, which is [`Info::THE_SHIP_APP_ID`] for [`InfoBuilder::the_ship`].
+            pub fn steam_app_id(mut self, id: Short) -> Self {
+                self.info.id = id;
+                self
+            }
+            pub fn players(mut self, players: Byte) -> Self {
+                self.info.players = players;
+                self
+            }
+            pub fn max_players(mut self, max_players: Byte) -> Self {
+                self.info.max_players = max_players;
+                self
+            }
+            pub fn bots(mut self, bots: Byte) -> Self {
+                self.info.bots = bots;
+                self
+            }
+            pub fn server_type(mut self, server_type: ServerType) -> Self {
+                self.info.server_type = server_type;
+                self
+            }
+            pub fn platform(mut self, platform: Platform) -> Self {
+                self.info.environment = platform;
+                self
+            }
+            pub fn visibility(mut self, visibility: Visibility) -> Self {
+                self.info.visibility = visibility;
+                self
+            }
+            pub fn vac(mut self, vac: Vac) -> Self {
+                self.info.vac = vac;
+                self
+            }
+            /// Game mode and settings of The Ship, also setting the AppID to The Ship's.
+            pub fn the_ship(mut self, the_ship: TheShipInfo) -> Self {
+                self.info.id = Info::THE_SHIP_APP_ID;
+                self.info.the_ship = Some(the_ship);
+                self
+            }
+            pub fn game_version(mut self, game_version: &str) -> Self {
+                self.info.game_version = game_version.to_string();
+                self
+            }
+            pub fn port(mut self, port: Short) -> Self {
+                self.info.port = Some(port);
+                self
+            }
+            pub fn steam_id(mut self, steam_id: LongLong) -> Self {
+                self.info.steam_id = Some(steam_id);
+                self
+            }
+            pub fn source_tv(mut self, source_tv: SourceTv) -> Self {
+                self.info.spectator_port = Some(source_tv.port);
+                self.info.spectator_name = Some(source_tv.name);
+                self
+            }
+            pub fn keywords(mut self, keywords: &str) -> Self {
+                self.info.keywords = Some(keywords.to_string());
+                self
+            }
+            pub fn game_id(mut self, game_id: LongLong) -> Self {
+                self.info.game_id = Some(game_id);
+                self
+            }
+
+            pub fn build(mut self) -> Info {
+                let info = &self.info;
+                let fields = [
+                    (info.port.is_some(), ExtraData::PORT),
+                    (info.steam_id.is_some(), ExtraData::STEAM_ID),
+                    (info.spectator_port.is_some(), ExtraData::SOURCE_TV),
+                    (info.keywords.is_some(), ExtraData::KEYWORDS),
+                    (info.game_id.is_some(), ExtraData::GAME_ID),
+                ];
+                let flag = fields
+                    .into_iter()
+                    .filter(|(present, _)| *present)
+                    .fold(0, |flag, (_, bit)| flag | bit);
+                self.info.extra_data_flag = (flag != 0).then_some(flag);
+                self.info
+            }
+        }
+
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+        pub enum ServerType {
+            Dedicated,
+            NonDedicated,
+            SourceTvRelay,
         }
 
         impl ServerType {
-            fn from_byte(byte: &u8) -> Self {
+            fn from_byte(byte: Byte, position: usize) -> Result<Self, QueryError> {
                 use self::ServerType::{Dedicated, NonDedicated, SourceTvRelay};
 
-                match *byte as char {
-                    'd' => Dedicated,
-                    'l' => NonDedicated,
-                    'p' => SourceTvRelay,
-                    _ => panic!("Unrecognized Server Type: <{byte}>."),
+                // Uppercase in the obsolete GoldSource response
+                Ok(match byte as char {
+                    'd' | 'D' => Dedicated,
+                    'l' | 'L' => NonDedicated,
+                    'p' | 'P' => SourceTvRelay,
+                    _ => {
+                        return Err(QueryError::UnexpectedByte {
+                            field: "server type",
+                            byte,
+                            position,
+                        })
+                    }
+                })
+            }
+
+            fn to_byte(&self) -> Byte {
+                match self {
+                    Self::Dedicated => b'd',
+                    Self::NonDedicated => b'l',
+                    Self::SourceTvRelay => b'p',
                 }
             }
         }
 
-        #[derive(Debug, Eq, PartialEq, Clone)]
+        /// Operating system of a server.
+        ///
+        /// Mac servers send one of two bytes, see [`Platform::is_mac`] to match either.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+        #[non_exhaustive]
         pub enum Platform {
             Linux,
             Windows,
+            /// Mac, sent as `'m'`
             Mac,
+            /// Mac, sent as `'o'`, the code having changed after Left 4 Dead
+            MacOs,
         }
 
         impl Platform {
-            fn from_byte(byte: &u8) -> Self {
-                use self::Platform::{Linux, Mac, Windows};
+            fn from_byte(byte: Byte, position: usize) -> Result<Self, QueryError> {
+                use self::Platform::{Linux, Mac, MacOs, Windows};
+
+                // Uppercase in the obsolete GoldSource response
+                Ok(match byte as char {
+                    'l' | 'L' => Linux,
+                    'w' | 'W' => Windows,
+                    'm' | 'M' => Mac,
+                    'o' | 'O' => MacOs,
+                    _ => {
+                        return Err(QueryError::UnexpectedByte {
+                            field: "environment",
+                            byte,
+                            position,
+                        })
+                    }
+                })
+            }
 
-                match *byte as char {
-                    'l' => Linux,
-                    'w' => Windows,
-                    'm' => Mac,
-                    'o' => Mac,
-                    _ => panic!("Unrecognized Environment: <{byte}>."),
+            /// Environment byte sent by the server, in lowercase.
+            pub const fn as_byte(&self) -> Byte {
+                match self {
+                    Self::Linux => b'l',
+                    Self::Windows => b'w',
+                    Self::Mac => b'm',
+                    Self::MacOs => b'o',
                 }
             }
+
+            /// Whether the server runs on Mac, whichever byte it was sent as.
+            pub fn is_mac(&self) -> bool {
+                matches!(self, Self::Mac | Self::MacOs)
+            }
         }
 
-        #[derive(Debug, Eq, PartialEq, Clone)]
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
         pub enum Visibility {
             Public,
             Private,
         }
 
         impl Visibility {
-            fn from_byte(byte: &u8) -> Self {
+            fn from_byte(byte: Byte, position: usize) -> Result<Self, QueryError> {
                 use self::Visibility::{Private, Public};
 
-                match *byte {
+                Ok(match byte {
                     0x00 => Public,
                     0x01 => Private,
-                    _ => panic!("Unrecognized Visibility Byte: <{byte}>."),
+                    _ => {
+                        return Err(QueryError::UnexpectedByte {
+                            field: "visibility",
+                            byte,
+                            position,
+                        })
+                    }
+                })
+            }
+
+            fn to_byte(&self) -> Byte {
+                match self {
+                    Self::Public => 0x00,
+                    Self::Private => 0x01,
                 }
             }
         }
 
-        #[derive(Debug, Eq, PartialEq, Clone)]
+        impl fmt::Display for Visibility {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    Self::Public => "public",
+                    Self::Private => "private",
+                })
+            }
+        }
+
+        #[derive(Debug, Eq, PartialEq, Hash, Clone)]
         /// Specifies if a server uses VAC.
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
         pub enum Vac {
             Unsecured,
             Secured,
         }
 
         impl Vac {
-            fn from_byte(byte: &u8) -> Self {
+            fn from_byte(byte: Byte, position: usize) -> Result<Self, QueryError> {
                 use self::Vac::{Secured, Unsecured};
 
-                match *byte {
+                Ok(match byte {
                     0x00 => Unsecured,
                     0x01 => Secured,
-                    _ => panic!("Unrecognized Vac Byte: <{byte}>."),
+                    _ => {
+                        return Err(QueryError::UnexpectedByte {
+                            field: "VAC",
+                            byte,
+                            position,
+                        })
+                    }
+                })
+            }
+
+            fn to_byte(&self) -> Byte {
+                match self {
+                    Self::Unsecured => 0x00,
+                    Self::Secured => 0x01,
                 }
             }
         }
 
+        impl fmt::Display for Vac {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    Self::Unsecured => "unsecured",
+                    Self::Secured => "secured",
+                })
+            }
+        }
+
+        /// Steam Application ID of a game.
+        ///
+        /// ```
+        /// use valve_server_query::AppId;
+        ///
+        /// assert_eq!(AppId(440).name(), Some("Team Fortress 2"));
+        /// assert_eq!(AppId(1).name(), None);
+        /// ```
+        #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct AppId(pub u32);
+
+        impl AppId {
+            /// Display name of a well-known game, or `None` for any other, in which case
+            /// [`Info::game`] describes the game instead.
+            pub fn name(&self) -> Option<&'static str> {
+                Some(match self.0 {
+                    10 => "Counter-Strike",
+                    20 => "Team Fortress Classic",
+                    30 => "Day of Defeat",
+                    40 => "Deathmatch Classic",
+                    50 => "Half-Life: Opposing Force",
+                    60 => "Ricochet",
+                    70 => "Half-Life",
+                    80 => "Counter-Strike: Condition Zero",
+                    240 => "Counter-Strike: Source",
+                    300 => "Day of Defeat: Source",
+                    320 => "Half-Life 2: Deathmatch",
+                    440 => "Team Fortress 2",
+                    500 => "Left 4 Dead",
+                    550 => "Left 4 Dead 2",
+                    630 => "Alien Swarm",
+                    730 => "Counter-Strike 2",
+                    2400 => "The Ship",
+                    4000 => "Garry's Mod",
+                    17500 => "Zombie Panic! Source",
+                    107410 => "Arma 3",
+                    221100 => "DayZ",
+                    222880 => "Insurgency",
+                    252490 => "Rust",
+                    346110 => "ARK: Survival Evolved",
+                    581320 => "Insurgency: Sandstorm",
+                    _ => return None,
+                })
+            }
+        }
+
+        impl From<u32> for AppId {
+            fn from(id: u32) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<AppId> for u32 {
+            fn from(id: AppId) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for AppId {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
         #[cfg(test)]
         mod tests {
             use super::*;
             #[test]
             fn test_servertype_from_byte() {
-                assert_eq!(ServerType::Dedicated, ServerType::from_byte(&('d' as u8)));
+                assert_eq!(
+                    ServerType::Dedicated,
+                    ServerType::from_byte(b'd', 0).unwrap()
+                );
             }
             #[test]
             fn test_environment_from_byte() {
-                assert_eq!(Platform::Linux, Platform::from_byte(&('l' as u8)));
+                assert_eq!(Platform::Linux, Platform::from_byte(b'l', 0).unwrap());
             }
             #[test]
             fn test_visibility_from_byte() {
-                assert_eq!(Visibility::Public, Visibility::from_byte(&(0x00)));
+                assert_eq!(Visibility::Public, Visibility::from_byte(0x00, 0).unwrap());
             }
             #[test]
             fn test_vac_from_byte() {
-                assert_eq!(Vac::Secured, Vac::from_byte(&(0x01)));
+                assert_eq!(Vac::Secured, Vac::from_byte(0x01, 0).unwrap());
             }
-        }
-    }
-}
-
-pub mod server {
-
-    use crate::{MULTI_PACKET_RESPONSE_HEADER, PACKET_SIZE, SIMPLE_RESPONSE_HEADER};
-    use std::collections::HashMap;
-    use std::error::Error;
-    use std::io;
-    use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
-    use std::time::Duration;
 
-    use crate::models::info::Info;
-    use crate::models::Player;
-    use crate::types::Byte;
-    use crate::utils::get_multipacket_data;
+            #[test]
+            fn test_info_unexpected_byte() {
+                // Server type, environment, visibility, VAC
+                for (offset, field, byte) in [
+                    (51, "server type", b'x'),
+                    (52, "environment", b'x'),
+                    (53, "visibility", 0x02),
+                    (54, "VAC", 0xFF),
+                ] {
+                    let mut bytes = info_payload();
+                    bytes[offset] = byte;
+                    assert!(matches!(
+                        Info::from_bytes(&bytes),
+                        Err(QueryError::UnexpectedByte { field: f, byte: b, position })
+                            if f == field && b == byte && position == offset
+                    ));
+                    assert!(InfoRef::from_bytes(&bytes).is_err());
+                }
+            }
 
-    pub type Rules = HashMap<String, String>;
+            /// A2S_INFO payload (without the simple response header) with no extra data.
+            fn info_payload() -> Vec<u8> {
+                let mut bytes = vec![0x49, 0x11];
+                bytes.extend(b"Test Server\0de_dust2\0cstrike\0Counter-Strike\0");
+                bytes.extend(10i16.to_le_bytes());
+                bytes.extend([5, 24, 0, b'd', b'l', 0x00, 0x01]);
+                bytes.extend(b"1.0.0.0\0");
+                bytes
+            }
 
-    /// Represents a game server running a Steam game.
-    ///
-    /// ```compile_fail
-    /// let server = Server::new("127.0.0.1:12345").expect("Connect to dedicated server running Valve game");
-    ///
-    /// let info = server.info().expect("Get general server information");
-    /// let players = server.players().expect("Get server player information");
-    /// let rules = server.rules().expect("Get server rules");
-    /// ```
-    #[derive(Debug)]
-    pub struct Server {
-        socket: UdpSocket,
-        addr: SocketAddr,
-    }
+            #[test]
+            fn test_info_from_bytes_header_and_protocol() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.header(), 0x49);
+                assert_eq!(info.protocol(), 0x11);
+                assert_eq!(info.name(), "Test Server");
+                assert_eq!(info.game_version(), "1.0.0.0");
+            }
 
-    impl Server {
-        pub fn new(url: &str) -> Result<Self, Box<dyn Error>> {
-            // Init
-            let addr: SocketAddr;
-            let socket: UdpSocket;
-
-            // Handle Errors
-            let result: Result<SocketAddr, _> = url.parse();
-            if let Ok(a) = result {
-                addr = a;
-            } else {
-                if let Err(e) = result {
-                    return Err(Box::new(e));
-                } else {
-                    unreachable!();
+            #[test]
+            fn test_info_fill_ratio() {
+                // (players, max_players, fill_ratio)
+                let table = [
+                    (5, 24, Some(5.0 / 24.0)),
+                    (0, 24, Some(0.0)),
+                    (24, 24, Some(1.0)),
+                    (30, 24, Some(1.0)),
+                    (0, 0, None),
+                    (3, 0, None),
+                ];
+                for (players, max_players, fill_ratio) in table {
+                    let mut bytes = info_payload();
+                    bytes[48] = players;
+                    bytes[49] = max_players;
+
+                    let info = Info::from_bytes(&bytes).unwrap();
+                    assert_eq!(*info.player_count(), players);
+                    assert_eq!(*info.player_max(), max_players);
+                    assert_eq!(info.fill_ratio(), fill_ratio);
                 }
             }
 
-            let result: Result<UdpSocket, _> =
-                UdpSocket::bind((IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0));
-            if let Ok(s) = result {
-                socket = s;
-            } else {
-                if let Err(e) = result {
-                    return Err(Box::new(e));
-                } else {
-                    unreachable!();
+            #[test]
+            fn test_info_slots() {
+                // (players, max_players, bots, humans, free, free excluding bots)
+                let table = [
+                    (0, 24, 0, 0, 24, 24),
+                    (5, 24, 0, 5, 19, 19),
+                    (24, 24, 0, 24, 0, 0),
+                    // Padded with bots
+                    (24, 24, 20, 4, 0, 20),
+                    (10, 10, 10, 0, 0, 10),
+                    // Misconfigured
+                    (30, 24, 0, 30, 0, 0),
+                    (2, 24, 5, 0, 22, 24),
+                    (0, 0, 0, 0, 0, 0),
+                ];
+                for (players, max_players, bots, humans, free, free_excluding_bots) in table {
+                    let mut bytes = info_payload();
+                    bytes[48] = players;
+                    bytes[49] = max_players;
+                    bytes[50] = bots;
+                    let info = Info::from_bytes(&bytes).unwrap();
+
+                    let case = (players, max_players, bots);
+                    assert_eq!(info.human_count(), humans, "{case:?}");
+                    assert_eq!(info.free_slots(), free, "{case:?}");
+                    assert_eq!(
+                        info.free_slots_excluding_bots(),
+                        free_excluding_bots,
+                        "{case:?}"
+                    );
+                    assert_eq!(info.is_full(), free == 0, "{case:?}");
+                    assert_eq!(
+                        info.is_full_excluding_bots(),
+                        free_excluding_bots == 0,
+                        "{case:?}"
+                    );
+                    assert_eq!(info.is_empty(), players == 0, "{case:?}");
+                    assert_eq!(info.is_empty_excluding_bots(), humans == 0, "{case:?}");
                 }
             }
 
-            // Socket Settings
-            socket.set_read_timeout(Some(Duration::from_secs(1)))?;
-            socket.set_write_timeout(Some(Duration::from_secs(1)))?;
-
-            // Return Successfully
-            Ok(Self { addr, socket })
-        }
-    }
-
-    /// Socket Settings
-    impl Server {
-        pub fn set_read_timeout(
-            &mut self,
-            duration: Option<Duration>,
-        ) -> Result<(), Box<dyn Error>> {
-            self.socket.set_read_timeout(duration)?;
-            Ok(())
-        }
-        pub fn set_write_timeout(
-            &mut self,
-            duration: Option<Duration>,
-        ) -> Result<(), Box<dyn Error>> {
-            self.socket.set_write_timeout(duration)?;
-            Ok(())
-        }
-    }
+            #[test]
+            fn test_info_password_and_vac() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert!(!info.has_password());
+                assert!(info.is_vac_secured());
+
+                let mut bytes = info_payload();
+                bytes[53] = 0x01;
+                bytes[54] = 0x00;
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert!(info.has_password());
+                assert!(!info.is_vac_secured());
+            }
 
-    // A2S_INFO Implementation
-    impl Server {
-        pub fn info(&self) -> Result<Info, io::Error> {
-            let mut request: Vec<u8> = vec![
-                255, 255, 255, 255, 84, 83, 111, 117, 114, 99, 101, 32, 69, 110, 103, 105, 110,
-                101, 32, 81, 117, 101, 114, 121, 0,
-            ];
+            #[test]
+            fn test_info_from_bytes_utf8() {
+                let mut bytes = vec![0x49, 0x11];
+                bytes.extend("テストサーバー\0карта\0cstrike\0Counter-Strike\0".as_bytes());
+                bytes.extend(10i16.to_le_bytes());
+                bytes.extend([5, 24, 0, b'd', b'l', 0x00, 0x01]);
+                bytes.extend(b"1.0.0.0\0");
+
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(info.name(), "テストサーバー");
+                assert_eq!(info.map(), "карта");
+            }
 
-            self.socket.send_to(&request, &self.addr)?;
+            #[test]
+            fn test_info_hash() {
+                use std::collections::HashSet;
+
+                let mut snapshots = HashSet::new();
+                assert!(snapshots.insert(Info::from_bytes(&info_payload()).unwrap()));
+                assert!(!snapshots.insert(Info::from_bytes(&info_payload()).unwrap()));
+
+                // A player joined
+                let mut bytes = info_payload();
+                bytes[48] += 1;
+                assert!(snapshots.insert(Info::from_bytes(&bytes).unwrap()));
+                assert_eq!(snapshots.len(), 2);
+
+                // Trailing bytes take part in equality
+                let mut bytes = info_payload();
+                bytes.push(0x00);
+                let without = Info::from_bytes(&bytes).unwrap();
+                bytes.extend(b"extra");
+                let with = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(with.trailing_bytes(), Some(&b"extra"[..]));
+                assert_ne!(with, without);
+                assert!(snapshots.insert(with));
+            }
 
-            let mut buffer = [0; PACKET_SIZE];
-            let mut bytes_returned = self.socket.recv(&mut buffer)?;
+            #[test]
+            fn test_info_ref_borrows_strings() {
+                let mut bytes = info_payload();
+                bytes.push(0x20 | 0x01);
+                bytes.extend(b"secure,dust\0");
+                bytes.extend(730u64.to_le_bytes());
+
+                let info = InfoRef::from_bytes(&bytes).unwrap();
+                assert!(matches!(info.name, Cow::Borrowed("Test Server")));
+                assert!(matches!(info.keywords, Some(Cow::Borrowed("secure,dust"))));
+                assert_eq!(info.game_id, Some(730));
+                assert_eq!(info.trailing_bytes, None);
+                assert_eq!(info.to_owned(), Info::from_bytes(&bytes).unwrap());
+
+                // Converted strings are owned
+                bytes[2] = 0xFF;
+                let info = InfoRef::from_bytes(&bytes).unwrap();
+                assert!(matches!(info.name, Cow::Owned(_)));
+                assert_eq!(info.name, "\u{FFFD}est Server");
+            }
 
-            if bytes_returned == 9 {
-                // Challenge Received
+            #[test]
+            fn test_extra_data_from_byte() {
+                let extra_data = ExtraData::from_byte(0x80 | 0x20 | 0x01);
+                assert_eq!(
+                    extra_data,
+                    ExtraData {
+                        has_port: true,
+                        has_steam_id: false,
+                        has_source_tv: false,
+                        has_keywords: true,
+                        has_game_id: true,
+                    }
+                );
+                assert_eq!(ExtraData::from_byte(0x00), ExtraData::default());
+            }
 
-                // Last 5 bytes of the response
-                let challenge = buffer
-                    .into_iter()
-                    .rev()
-                    .skip_while(|&i| i == 0)
-                    .collect::<Vec<u8>>()
-                    .to_owned()
-                    .into_iter()
-                    .rev()
-                    .collect::<Vec<u8>>()[5..]
-                    .to_vec();
+            #[test]
+            fn test_info_extra_data() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.extra_data(), None);
+
+                let mut bytes = info_payload();
+                bytes.push(0x80 | 0x20);
+                bytes.extend(27015i16.to_le_bytes());
+                bytes.extend(b"alltalk,nocrits\x00");
+                let info = Info::from_bytes(&bytes).unwrap();
+
+                let extra_data = info.extra_data().unwrap();
+                assert!(extra_data.has_port);
+                assert!(extra_data.has_keywords);
+                assert!(!extra_data.has_steam_id);
+                assert_eq!(info.port(), &Some(27015));
+                assert_eq!(info.keywords().as_deref(), Some("alltalk,nocrits"));
+            }
 
-                request.extend(challenge);
+            #[test]
+            fn test_info_extra_data_fields() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.protocol(), 0x11);
+                assert_eq!(info.extra_data_flag(), None);
+                assert!(!info.has_port_field());
+                assert!(!info.has_game_id_field());
+
+                // (flag, fields, [port, steam_id, source_tv, keywords, game_id])
+                let table: [(Byte, &[u8], [bool; 5]); 4] = [
+                    (0x00, &[], [false; 5]),
+                    (0x80, &[0x87, 0x69], [true, false, false, false, false]),
+                    (
+                        0x10 | 0x01,
+                        &[1, 0, 0, 0, 0, 0, 0, 0, 0xDA, 0x02, 0, 0, 0, 0, 0, 0],
+                        [false, true, false, false, true],
+                    ),
+                    (
+                        0x40 | 0x20,
+                        &[0x88, 0x69, b'T', b'V', 0x00, b'a', 0x00],
+                        [false, false, true, true, false],
+                    ),
+                ];
+                for (flag, fields, expected) in table {
+                    let mut bytes = info_payload();
+                    bytes.push(flag);
+                    bytes.extend(fields);
+                    let info = Info::from_bytes(&bytes).unwrap();
+
+                    assert_eq!(info.extra_data_flag(), Some(flag));
+                    let found = [
+                        info.has_port_field(),
+                        info.has_steam_id_field(),
+                        info.has_source_tv_field(),
+                        info.has_keywords_field(),
+                        info.has_game_id_field(),
+                    ];
+                    assert_eq!(found, expected, "flag {flag:#04X}");
+                    assert_eq!(info.port().is_some(), expected[0]);
+                    assert_eq!(info.steam_id().is_some(), expected[1]);
+                    assert_eq!(info.keywords().is_some(), expected[3]);
+                    assert_eq!(info.game_id().is_some(), expected[4]);
+                }
 
-                self.socket.send_to(&request, &self.addr)?;
-                buffer = [0; PACKET_SIZE];
-                bytes_returned = self.socket.recv(&mut buffer)?;
+                // Declared, but cut short
+                let mut bytes = info_payload();
+                bytes.push(0x80);
+                assert!(matches!(
+                    Info::from_bytes(&bytes),
+                    Err(QueryError::UnexpectedEnd { needed: 2, .. })
+                ));
             }
 
-            let packet_header = &buffer[..4];
-            let payload: Vec<u8>;
-
-            if packet_header == SIMPLE_RESPONSE_HEADER {
-                payload = buffer[4..bytes_returned + 1].to_vec();
-            } else if packet_header == MULTI_PACKET_RESPONSE_HEADER {
-                // id starts at 0
-                // tcp means they don't have to be in order
-                let (_answer_id, total, packet_id) = get_multipacket_data(&buffer);
-                let mut packet_map: HashMap<Byte, Vec<u8>> = HashMap::with_capacity(total as usize);
+            #[test]
+            fn test_info_keywords_list() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert!(info.keywords_list().is_empty());
+                assert!(!info.has_keyword("alltalk"));
+
+                let table: [(&[u8], &[&str]); 5] = [
+                    // Team Fortress 2
+                    (
+                        b"alltalk,increased_maxplayers,nocrits",
+                        &["alltalk", "increased_maxplayers", "nocrits"],
+                    ),
+                    // Rust
+                    (b"mp120,cp37,qp0,v2405", &["mp120", "cp37", "qp0", "v2405"]),
+                    (b"alltalk, nocrits,", &["alltalk", "nocrits"]),
+                    (b"", &[]),
+                    (b" , ,", &[]),
+                ];
+                for (keywords, expected) in table {
+                    let mut bytes = info_payload();
+                    bytes.push(0x20);
+                    bytes.extend(keywords);
+                    bytes.push(0x00);
+                    let info = Info::from_bytes(&bytes).unwrap();
+                    assert_eq!(info.keywords_list(), expected);
+                }
 
-                let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                packet_map.insert(packet_id, current_payload);
+                let mut bytes = info_payload();
+                bytes.push(0x20);
+                bytes.extend(b"alltalk,increased_maxplayers,nocrits\0");
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert!(info.has_keyword("nocrits"));
+                assert!(info.has_keyword("AllTalk"));
+                assert!(!info.has_keyword("crits"));
+                assert!(!info.has_keyword(""));
+            }
 
-                // Get the remaining packet data.
-                while total > packet_map.len() as u8 {
-                    buffer = [0; PACKET_SIZE]; // Clear buffer
-                    bytes_returned = self.socket.recv(&mut buffer)?;
+            #[test]
+            fn test_info_app_id() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.app_id(), 10);
+                assert_eq!(info.app(), AppId(10));
+                assert_eq!(info.game_name(), Some("Counter-Strike"));
+
+                // Truncated to 16 bits, and recovered from the GameID
+                let mut bytes = info_payload();
+                bytes[46..48].copy_from_slice(&(252490u32 as i16).to_le_bytes());
+                assert_eq!(Info::from_bytes(&bytes).unwrap().app_id(), 252490 & 0xFFFF);
+                bytes.push(0x01);
+                bytes.extend(252490u64.to_le_bytes());
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(info.app_id(), 252490);
+                assert_eq!(info.game_name(), Some("Rust"));
+
+                let mut bytes = info_payload();
+                bytes[46..48].copy_from_slice(&1i16.to_le_bytes());
+                assert_eq!(Info::from_bytes(&bytes).unwrap().game_name(), None);
+
+                // Above 32767, without a GameID, the 16-bit field reads as negative
+                let mut bytes = info_payload();
+                bytes[46..48].copy_from_slice(&40000u16.to_le_bytes());
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert!(*info.steam_app_id() < 0);
+                assert_eq!(info.app_id(), 40000);
+            }
 
-                    let (_answer_id, _total, packet_id) = get_multipacket_data(&buffer);
-                    let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                    packet_map.insert(packet_id, current_payload);
+            #[test]
+            fn test_info_game_id_flags() {
+                assert_eq!(
+                    Info::from_bytes(&info_payload()).unwrap().game_id_flags(),
+                    None
+                );
+
+                // (16-bit AppID, GameID, AppID, flags)
+                let table = [
+                    // Team Fortress 2
+                    (440, 440u64, 440, 0),
+                    // V Rising, above 65535
+                    (1604030u32 as i16, 1604030, 1604030, 0),
+                    // A mod, with its type and ID above the AppID
+                    (
+                        218,
+                        (0x8A2B_1C3D << 32) | (0x01 << 24) | 218,
+                        218,
+                        (0x8A2B_1C3D << 8) | 0x01,
+                    ),
+                ];
+                for (id, game_id, app_id, flags) in table {
+                    let mut bytes = info_payload();
+                    bytes[46..48].copy_from_slice(&id.to_le_bytes());
+                    bytes.push(0x01);
+                    bytes.extend(game_id.to_le_bytes());
+                    let info = Info::from_bytes(&bytes).unwrap();
+
+                    assert_eq!(info.app_id(), app_id);
+                    assert_eq!(info.game_id_flags(), Some(flags));
                 }
+            }
 
-                // Sort and Collect all packet data
-                let mut v: Vec<(u8, Vec<u8>)> = packet_map.into_iter().collect();
-                v.sort_by_key(|i| i.0);
-                payload = v
-                    .into_iter()
-                    .map(|(_, bytes)| bytes)
-                    .flatten()
-                    .collect::<Vec<u8>>();
-            } else {
-                panic!("An unknown packet header was received.");
+            #[test]
+            fn test_info_trailing_bytes() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.trailing_bytes(), None);
+                assert_eq!(info.trailing_bytes_len(), 0);
+
+                // Unknown fields after the extra data
+                let mut bytes = info_payload();
+                bytes.push(0x80 | 0x01);
+                bytes.extend(27015i16.to_le_bytes());
+                bytes.extend(730u64.to_le_bytes());
+                bytes.extend([0x2A, 0x00, 0x07, 0x00, 0x00, 0x00]);
+                let info = Info::from_bytes(&bytes).unwrap();
+
+                assert_eq!(info.port(), &Some(27015));
+                assert_eq!(info.game_id(), &Some(730));
+                assert_eq!(
+                    info.trailing_bytes(),
+                    Some(&[0x2A, 0x00, 0x07, 0x00, 0x00, 0x00][..])
+                );
+                assert_eq!(info.trailing_bytes_len(), 6);
+
+                // Null bytes are unparsed bytes too
+                let mut bytes = info_payload();
+                bytes.extend([0x00, 0x00, 0x00]);
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(info.trailing_bytes(), Some(&[0x00, 0x00][..]));
             }
 
-            let info = Info::from_bytes(&payload);
-            Ok(info)
-        }
-    }
+            #[test]
+            fn test_info_parse_consumed() {
+                // Without the Extra Data Flag
+                let bytes = info_payload();
+                assert_eq!(Info::parse(&bytes).unwrap().1, 63);
+                assert_eq!(bytes.len(), 63);
+
+                // Extra Data Flag, port and GameID, then unknown fields
+                let mut bytes = info_payload();
+                bytes.push(0x80 | 0x01);
+                bytes.extend(27015i16.to_le_bytes());
+                bytes.extend(730u64.to_le_bytes());
+                bytes.extend([0x2A, 0x00, 0x07]);
+                let (info, consumed) = Info::parse(&bytes).unwrap();
+                assert_eq!(consumed, 63 + 1 + 2 + 8);
+                assert_eq!(&bytes[consumed..], info.trailing_bytes().unwrap());
+
+                // The Extra Data Flag alone, declaring no fields
+                let mut bytes = info_payload();
+                bytes.push(0x00);
+                assert_eq!(Info::parse(&bytes).unwrap().1, 64);
+            }
 
-    // A2S_PLAYER Implementation
-    impl Server {
-        pub fn players(&self) -> Result<Vec<Player>, io::Error> {
-            let request = [
-                0xFF, 0xFF, 0xFF, 0xFF, // Simple Header
-                0x55, // Header
-                0xFF, 0xFF, 0xFF, 0xFF, // Request Challenge
-            ];
+            #[test]
+            fn test_info_ends_with_empty_strings() {
+                // Empty SourceTV name and keywords: the payload ends with two null bytes
+                let mut bytes = info_payload();
+                bytes.push(0x40 | 0x20);
+                bytes.extend(27020i16.to_le_bytes());
+                bytes.extend([0x00, 0x00]);
+
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(info.spectator_port(), &Some(27020));
+                assert_eq!(info.spectator_name().as_deref(), Some(""));
+                assert_eq!(info.keywords().as_deref(), Some(""));
+                assert_eq!(info.trailing_bytes(), None);
+
+                // An empty string cut short is an error, not padding
+                assert!(Info::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+            }
 
-            self.socket.send_to(&request, &self.addr)?;
+            #[test]
+            fn test_info_game_port() {
+                let queried: SocketAddr = "203.0.113.7:27016".parse().unwrap();
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.game_port(), None);
+                assert_eq!(info.game_address(queried), queried);
+
+                // (wire bytes, port, game_port): little-endian, 27015 is 0x6987
+                let table = [
+                    ([0x87, 0x69], 27015, 27015),
+                    ([0x40, 0x9C], -25536, 40000),
+                    ([0xFF, 0xFF], -1, 65535),
+                ];
+                for (wire, port, game_port) in table {
+                    let mut bytes = info_payload();
+                    bytes.push(0x80);
+                    bytes.extend(wire);
+                    let info = Info::from_bytes(&bytes).unwrap();
+
+                    assert_eq!(info.port(), &Some(port));
+                    assert_eq!(info.game_port(), Some(game_port));
+                    assert_eq!(
+                        info.game_address(queried),
+                        SocketAddr::new(queried.ip(), game_port)
+                    );
+                }
 
-            let mut buffer = [0; PACKET_SIZE];
-            let _bytes_returned = self.socket.recv(&mut buffer)?;
+                let info = Info::from_bytes(&goldsource_info_payload()).unwrap();
+                assert_eq!(
+                    info.game_address(queried),
+                    "192.168.1.10:27015".parse().unwrap()
+                );
+            }
 
-            //  Get Challenge
-            let challenge = buffer
-                .into_iter()
-                .rev()
-                .skip_while(|&i| i == 0)
-                .collect::<Vec<u8>>()
-                .to_owned()
-                .into_iter()
-                .rev()
-                .collect::<Vec<u8>>()[5..]
-                .to_vec();
-
-            // Resend Request
-            let mut request = vec![
-                0xFF, 0xFF, 0xFF, 0xFF, // Simple Header
-                0x55, // Header
-            ];
-            request.extend(challenge);
-
-            // Get Data
-            self.socket.send_to(&request, &self.addr)?;
-            buffer = [0; PACKET_SIZE];
-            let mut bytes_returned = self.socket.recv(&mut buffer)?;
-
-            // Parse Data
-            let packet_header = &buffer[..=3];
-
-            let payload: Vec<u8>;
-            if packet_header == SIMPLE_RESPONSE_HEADER {
-                payload = buffer[4..].to_vec();
-            } else if packet_header == MULTI_PACKET_RESPONSE_HEADER {
-                // id starts at 0
-                // tcp means they don't have to be in order
-                let (_answer_id, total, packet_id) = get_multipacket_data(&buffer);
-                let mut packet_map: HashMap<Byte, Vec<u8>> = HashMap::with_capacity(total as usize);
-
-                let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                packet_map.insert(packet_id, current_payload);
-
-                // Get the remaining packet data.
-                while total > packet_map.len() as u8 {
-                    buffer = [0; PACKET_SIZE]; // Clear buffer
-                    bytes_returned = self.socket.recv(&mut buffer)?;
-
-                    let (_answer_id, _total, packet_id) = get_multipacket_data(&buffer);
-                    let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                    packet_map.insert(packet_id, current_payload);
-                }
-
-                // Sort and Collect all packet data
-                let mut v: Vec<(u8, Vec<u8>)> = packet_map.into_iter().collect();
-                v.sort_by_key(|i| i.0);
-                payload = v
-                    .into_iter()
-                    .map(|(_, bytes)| bytes)
-                    .flatten()
-                    .collect::<Vec<u8>>();
-            } else {
-                panic!("An unknown packet header was received.");
+            #[test]
+            fn test_info_source_tv() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.source_tv(), None);
+
+                let mut bytes = info_payload();
+                bytes.push(0x40);
+                bytes.extend(27020i16.to_le_bytes());
+                bytes.extend(b"SourceTV\x00");
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(
+                    info.source_tv(),
+                    Some(SourceTv {
+                        port: 27020,
+                        name: "SourceTV".to_string(),
+                    })
+                );
             }
 
-            let _header: &Byte = &payload[0];
-            let player_count: Byte = payload[1].clone();
+            #[test]
+            fn test_protocol_from_header() {
+                assert_eq!(Protocol::from_header(0x49), Some(Protocol::Source));
+                assert_eq!(Protocol::from_header(0x6D), Some(Protocol::GoldSource));
+                assert_eq!(Protocol::from_header(0x44), None);
 
-            let mut it = payload[2..].iter();
-            let mut players: Vec<Player> = Vec::new();
-            for _ in 0..player_count {
-                let player = Player::from_iter_bytes(&mut it);
-                players.push(player);
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.query_protocol(), Protocol::Source);
             }
 
-            Ok(players)
-        }
-    }
+            /// Obsolete GoldSource A2S_INFO payload, for a server running a mod.
+            fn goldsource_info_payload() -> Vec<u8> {
+                let mut bytes = vec![0x6D];
+                bytes.extend(b"192.168.1.10:27015\x00");
+                bytes.extend(b"GoldSource Server\x00crossfire\x00valve\x00Half-Life\x00");
+                bytes.extend([4, 16, 47, b'D', b'L', 0x00]);
+                // Mod
+                bytes.push(0x01);
+                bytes.extend(b"http://example.com\x00http://example.com/dl\x00");
+                bytes.push(0x00);
+                bytes.extend(2i32.to_le_bytes());
+                bytes.extend(1_048_576i32.to_le_bytes());
+                bytes.extend([0x01, 0x00]);
+                // VAC, Bots
+                bytes.extend([0x01, 2]);
+                bytes
+            }
 
-    /// A2S_RULES Implementation
-    impl Server {
-        pub fn rules(&self) -> Result<Rules, io::Error> {
-            use crate::utils::compress_trailing_null_bytes;
+            #[test]
+            fn test_info_from_goldsource_bytes() {
+                let info = Info::from_bytes(&goldsource_info_payload()).unwrap();
+
+                assert_eq!(info.query_protocol(), Protocol::GoldSource);
+                assert_eq!(info.address(), Some("192.168.1.10:27015"));
+                assert_eq!(info.name(), "GoldSource Server");
+                assert_eq!(info.map(), "crossfire");
+                assert_eq!(info.folder(), "valve");
+                assert_eq!(info.game(), "Half-Life");
+                assert_eq!(info.player_count(), &4);
+                assert_eq!(info.player_max(), &16);
+                assert_eq!(info.protocol(), 47);
+                assert_eq!(info.server_type(), &ServerType::Dedicated);
+                assert_eq!(info.platform(), &Platform::Linux);
+                assert_eq!(info.visibility(), &Visibility::Public);
+                assert_eq!(info.vac(), &Vac::Secured);
+                assert_eq!(info.bot_count(), &2);
+                assert_eq!(
+                    info.mod_info(),
+                    Some(&ModInfo {
+                        link: "http://example.com".to_string(),
+                        download_link: "http://example.com/dl".to_string(),
+                        version: 2,
+                        size: 1_048_576,
+                        multiplayer_only: true,
+                        custom_dll: false,
+                    })
+                );
+            }
 
-            let request = [
-                0xFF, 0xFF, 0xFF, 0xFF, // Simple Header
-                0x56, // Header
-                0xFF, 0xFF, 0xFF, 0xFF, // Request Challenge
-            ];
+            #[test]
+            fn test_info_from_goldsource_bytes_without_mod() {
+                let mut bytes = vec![0x6D];
+                bytes.extend(b"10.0.0.1:27015\x00Server\x00de_dust\x00cstrike\x00CS\x00");
+                bytes.extend([0, 32, 47, b'L', b'W', 0x01, 0x00, 0x00, 0]);
+
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(info.mod_info(), None);
+                assert_eq!(info.server_type(), &ServerType::NonDedicated);
+                assert_eq!(info.platform(), &Platform::Windows);
+                assert_eq!(info.visibility(), &Visibility::Private);
+                assert_eq!(info.vac(), &Vac::Unsecured);
+            }
 
-            self.socket.send_to(&request, &self.addr)?;
+            #[test]
+            fn test_platform_mac() {
+                for (byte, platform) in [(b'm', Platform::Mac), (b'o', Platform::MacOs)] {
+                    let mut bytes = info_payload();
+                    bytes[52] = byte;
+
+                    let info = Info::from_bytes(&bytes).unwrap();
+                    assert_eq!(info.platform(), &platform);
+                    assert_eq!(info.platform().as_byte(), byte);
+                    assert!(info.platform().is_mac());
+                    assert_eq!(info.to_bytes(), bytes);
+                }
+                assert!(!Platform::Linux.is_mac());
+                assert_eq!(Platform::Windows.as_byte(), b'w');
+            }
 
-            let mut buffer = [0; PACKET_SIZE];
-            let _bytes_returned = self.socket.recv(&mut buffer)?;
+            #[test]
+            #[cfg(feature = "serde")]
+            fn test_info_json() {
+                let info = Info::builder()
+                    .name("test")
+                    .map("de_dust2")
+                    .folder("cstrike")
+                    .game("Counter-Strike: Source")
+                    .steam_app_id(240)
+                    .players(10)
+                    .max_players(24)
+                    .vac(Vac::Secured)
+                    .game_version("1.0.0.22")
+                    .port(27015)
+                    .keywords("secure")
+                    .build();
+
+                // Fields that are `None` are left out
+                let json = serde_json::to_value(&info).unwrap();
+                assert_eq!(
+                    json,
+                    serde_json::json!({
+                        "query_protocol": "source",
+                        "protocol": 0x11,
+                        "name": "test",
+                        "map": "de_dust2",
+                        "folder": "cstrike",
+                        "game": "Counter-Strike: Source",
+                        "steam_app_id": 240,
+                        "player_count": 10,
+                        "player_max": 24,
+                        "bot_count": 0,
+                        "server_type": "dedicated",
+                        "platform": "linux",
+                        "visibility": "public",
+                        "vac": "secured",
+                        "game_version": "1.0.0.22",
+                        "extra_data_flag": 0x80 | 0x20,
+                        "port": 27015,
+                        "keywords": "secure",
+                    })
+                );
+                assert_eq!(serde_json::from_value::<Info>(json.clone()).unwrap(), info);
+
+                // Fields that do not agree as in a response
+                for (field, value) in [
+                    ("query_protocol", serde_json::json!(0)),
+                    ("query_protocol", serde_json::json!("steam")),
+                    ("extra_data_flag", serde_json::json!(0x80)),
+                    ("game_id", serde_json::json!(730)),
+                    ("steam_app_id", serde_json::json!(Info::THE_SHIP_APP_ID)),
+                    ("address", serde_json::json!("10.0.0.1:27015")),
+                ] {
+                    let mut json = json.clone();
+                    json[field] = value;
+                    assert!(
+                        serde_json::from_value::<Info>(json).is_err(),
+                        "{field} accepted"
+                    );
+                }
 
-            //  Get Challenge
-            let challenge = buffer
-                .into_iter()
-                .rev()
-                .skip_while(|&i| i == 0)
-                .collect::<Vec<u8>>()
-                .to_owned()
-                .into_iter()
-                .rev()
-                .collect::<Vec<u8>>()[5..]
-                .to_vec();
-
-            // Resend Request
-            let mut request = vec![
-                0xFF, 0xFF, 0xFF, 0xFF, // Simple Header
-                0x56, // Header
-            ];
-            request.extend(challenge);
-
-            // Get Data
-            self.socket.send_to(&request, &self.addr)?;
-            buffer = [0; PACKET_SIZE];
-            let mut bytes_returned = self.socket.recv(&mut buffer)?;
-
-            // Parse Data
-            let packet_header = &buffer[..=3];
-            let _header: &Byte = &buffer[4];
-
-            let mut payload: Vec<u8>;
-            if packet_header == SIMPLE_RESPONSE_HEADER {
-                let _rule_count: Byte = buffer[5].clone();
-                let _ = buffer[6]; // Null Byte
-                payload = buffer[7..].to_vec();
-                compress_trailing_null_bytes(&mut payload);
-            } else if packet_header == MULTI_PACKET_RESPONSE_HEADER {
-                // id starts at 0
-                // tcp means they don't have to be in order
-                let (_answer_id, total, packet_id) = get_multipacket_data(&buffer);
-                let mut packet_map: HashMap<Byte, Vec<u8>> = HashMap::with_capacity(total as usize);
-
-                let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                packet_map.insert(packet_id, current_payload);
-
-                // Get the remaining packet data.
-                while total > packet_map.len() as u8 {
-                    buffer = [0; PACKET_SIZE]; // Clear buffer
-                    bytes_returned = self.socket.recv(&mut buffer)?;
-
-                    let (_answer_id, _total, packet_id) = get_multipacket_data(&buffer);
-                    let current_payload = buffer[(4 + 4 + 1 + 1)..bytes_returned + 1].to_vec();
-                    packet_map.insert(packet_id, current_payload);
-                }
-
-                // Sort and Collect all packet data
-                let mut v: Vec<(u8, Vec<u8>)> = packet_map.into_iter().collect();
-                v.sort_by_key(|i| i.0);
-                payload = v
-                    .into_iter()
-                    .map(|(_, bytes)| bytes)
-                    .flatten()
-                    .collect::<Vec<u8>>();
-            } else {
-                panic!("An unknown packet header was received.");
+                let info = Info::from_bytes(&goldsource_info_payload()).unwrap();
+                let json = serde_json::to_value(&info).unwrap();
+                assert_eq!(json["address"], "192.168.1.10:27015");
+                assert_eq!(serde_json::from_value::<Info>(json).unwrap(), info);
+
+                let info = Info::builder()
+                    .server_type(ServerType::SourceTvRelay)
+                    .platform(Platform::MacOs)
+                    .visibility(Visibility::Private)
+                    .build();
+                let json = serde_json::to_value(&info).unwrap();
+                assert_eq!(json["server_type"], "sourcetvrelay");
+                assert_eq!(json["platform"], "macos");
+                assert_eq!(json["visibility"], "private");
+                assert_eq!(json["vac"], "unsecured");
+                assert_eq!(serde_json::from_value::<Info>(json).unwrap(), info);
             }
 
-            let rules: Rules = Self::get_rules(&payload);
-
-            Ok(rules)
-        }
+            #[test]
+            fn test_info_diff() {
+                let info = Info::builder()
+                    .name("test")
+                    .map("de_dust2")
+                    .players(12)
+                    .max_players(24)
+                    .keywords("secure")
+                    .build();
+
+                // No change
+                let delta = info.diff(&info.clone());
+                assert!(delta.is_empty());
+                assert_eq!(delta, InfoDelta::default());
+                assert_eq!(delta.to_string(), "");
+
+                // Single field
+                let joined = Info::builder()
+                    .name("test")
+                    .map("de_dust2")
+                    .players(17)
+                    .max_players(24)
+                    .keywords("secure")
+                    .build();
+                let delta = info.diff(&joined);
+                assert!(!delta.is_empty());
+                assert_eq!(delta.players, Some((12, 17)));
+                assert_eq!(delta.map, None);
+                assert_eq!(delta.to_string(), "players: 12 → 17");
+
+                // Several fields
+                let other = Info::builder()
+                    .name("test")
+                    .map("de_nuke")
+                    .players(17)
+                    .max_players(24)
+                    .visibility(Visibility::Private)
+                    .vac(Vac::Secured)
+                    .build();
+                let delta = info.diff(&other);
+                assert_eq!(
+                    delta.map,
+                    Some(("de_dust2".to_string(), "de_nuke".to_string()))
+                );
+                assert_eq!(
+                    delta.visibility,
+                    Some((Visibility::Public, Visibility::Private))
+                );
+                assert_eq!(delta.keywords, Some((Some("secure".to_string()), None)));
+                assert_eq!(delta.name, None);
+                assert_eq!(
+                    delta.to_string(),
+                    "map: de_dust2 → de_nuke, players: 12 → 17, visibility: public → private, \
+                     vac: unsecured → secured, keywords: secure → (none)"
+                );
+            }
 
-        pub fn get_rules(bytes: &[u8]) -> Rules {
-            use crate::types::get_string;
+            #[test]
+            fn test_info_builder() {
+                let info = Info::builder()
+                    .name("test")
+                    .map("de_dust2")
+                    .players(10)
+                    .max_players(24)
+                    .bots(2)
+                    .build();
+
+                assert_eq!(info.name(), "test");
+                assert_eq!(info.map(), "de_dust2");
+                assert_eq!(info.player_count(), &10);
+                assert_eq!(info.player_max(), &24);
+                assert_eq!(info.bot_count(), &2);
+                assert_eq!(info.extra_data_flag(), None);
+                assert_eq!(info.server_type(), &ServerType::Dedicated);
+                assert_eq!(info.vac(), &Vac::Unsecured);
+
+                let info = Info::builder()
+                    .port(27015)
+                    .keywords("secure,casual")
+                    .game_id(730)
+                    .build();
+                assert_eq!(
+                    info.extra_data_flag(),
+                    Some(ExtraData::PORT | ExtraData::KEYWORDS | ExtraData::GAME_ID)
+                );
+                assert_eq!(info.port(), &Some(27015));
+            }
 
-            let mut it = bytes.iter();
-            let mut rules = HashMap::new();
+            #[test]
+            fn test_info_to_bytes() {
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.to_bytes(), info_payload());
+
+                let info = Info::builder()
+                    .name("Ship Server")
+                    .the_ship(TheShipInfo {
+                        mode: 0x01,
+                        witnesses: 3,
+                        duration: 15,
+                    })
+                    .platform(Platform::Windows)
+                    .vac(Vac::Secured)
+                    .port(27015)
+                    .steam_id(76561198000000000)
+                    .source_tv(SourceTv {
+                        port: 27020,
+                        name: "SourceTV".to_string(),
+                    })
+                    .keywords("ship")
+                    .game_id(2400)
+                    .build();
+                assert_eq!(Info::from_bytes(&info.to_bytes()).unwrap(), info);
+
+                let info = Info::from_bytes(&goldsource_info_payload()).unwrap();
+                assert_eq!(Info::from_bytes(&info.to_bytes()).unwrap(), info);
+
+                // GoldSource Mac servers, in either case
+                for (byte, platform) in [
+                    (b'm', Platform::Mac),
+                    (b'o', Platform::MacOs),
+                    (b'M', Platform::Mac),
+                    (b'O', Platform::MacOs),
+                ] {
+                    let mut bytes = goldsource_info_payload();
+                    bytes[68] = byte;
+                    let info = Info::from_bytes(&bytes).unwrap();
+                    assert_eq!(info.platform(), &platform);
+                    assert_eq!(Info::from_bytes(&info.to_bytes()).unwrap(), info);
+                }
+            }
 
-            while it.len() > 0 {
-                let name = get_string(&mut it);
-                let value = get_string(&mut it);
+            #[test]
+            fn test_info_from_bytes_the_ship() {
+                let mut bytes = vec![0x49, 0x07];
+                bytes.extend(b"Ship Server\0batavier\0ship\0The Ship\0");
+                bytes.extend(2400i16.to_le_bytes());
+                bytes.extend([3, 16, 0, b'd', b'w', 0x00, 0x01]);
+                // Mode, Witnesses, Duration
+                bytes.extend([0x01, 3, 15]);
+                bytes.extend(b"1.0.0.4\0");
+                bytes.push(0x80);
+                bytes.extend(27015i16.to_le_bytes());
+
+                let info = Info::from_bytes(&bytes).unwrap();
+                assert_eq!(
+                    info.the_ship(),
+                    Some(&TheShipInfo {
+                        mode: 0x01,
+                        witnesses: 3,
+                        duration: 15,
+                    })
+                );
+                assert_eq!(info.game_version(), "1.0.0.4");
+                assert_eq!(info.extra_data_flag, Some(0x80));
+                assert_eq!(info.port(), &Some(27015));
+
+                let info = Info::from_bytes(&info_payload()).unwrap();
+                assert_eq!(info.the_ship(), None);
+            }
 
-                rules.insert(name, value);
+            #[test]
+            fn test_info_from_bytes_truncated() {
+                let bytes = info_payload();
+                // Cut off within the AppID
+                let truncated = &bytes[..bytes.len() - 16];
+                assert!(matches!(
+                    Info::from_bytes(truncated),
+                    Err(QueryError::UnexpectedEnd {
+                        position: 46,
+                        needed: 2
+                    })
+                ));
             }
 
-            rules
-        }
+            #[test]
+            fn test_info_from_bytes_unexpected_header() {
+                let mut bytes = info_payload();
+                bytes[0] = 0x44;
+                assert!(matches!(
+                    Info::from_bytes(&bytes),
+                    Err(QueryError::UnexpectedHeader {
+                        expected: 0x49,
+                        found: 0x44
+                    })
+                ));
+            }
+        }
+    }
+
+    /// Rules of DayZ (AppID 221100) and Arma 3 servers, which carry a binary description of
+    /// the DLC and mods the server runs alongside their plain rules.
+    ///
+    /// The binary data is split across rules whose keys are two bytes, the chunk's 1-based
+    /// index and the chunk count. The values are escaped so they contain no null bytes.
+    ///
+    /// Ref: <https://community.bistudio.com/wiki/Arma_3:_ServerBrowserProtocol3>
+    pub mod dayz {
+
+        use crate::error::QueryError;
+        use crate::protocol::{ParseOptions, ResponseKind};
+        use crate::server::Rules;
+        use crate::types::{Byte, PacketReader};
+
+        /// Steam Application ID of DayZ.
+        pub const APP_ID: u32 = 221100;
+
+        /// Rules of a DayZ server, with the binary mod descriptors decoded.
+        #[derive(Debug, Default, Eq, PartialEq, Clone)]
+        pub struct DayZRules {
+            /// Version of the binary encoding.
+            pub version: Byte,
+            /// Flags set when the mod or signature lists were too long to send in full.
+            pub overflow: Byte,
+            /// Flags of the DLC the server requires.
+            pub dlc_flags: u16,
+            /// Difficulty settings: AI level, difficulty level, flight model and third person.
+            pub difficulty: Byte,
+            /// Whether the crosshair is enabled.
+            pub crosshair: Byte,
+            /// Hash of each DLC flagged in `dlc_flags`.
+            pub dlc_hashes: Vec<u32>,
+            /// Mods the server runs.
+            pub mods: Vec<DayZMod>,
+            /// Names of the signature keys the server accepts.
+            pub signatures: Vec<String>,
+            /// The plain text rules sent alongside the binary data.
+            pub rules: Rules,
+        }
+
+        /// A mod or DLC loaded by a DayZ server.
+        #[derive(Debug, Default, Eq, PartialEq, Clone)]
+        pub struct DayZMod {
+            /// Hash of the mod's files.
+            pub hash: u32,
+            /// Steam Workshop ID of the mod, or AppID of a DLC.
+            pub workshop_id: u64,
+            /// Whether this is a DLC rather than a Workshop mod.
+            pub is_dlc: bool,
+            /// Name of the mod.
+            pub name: String,
+        }
+
+        /// Flag marking a mod as a DLC, alongside the length of its ID.
+        const DLC_FLAG: Byte = 0x10;
+
+        impl DayZRules {
+            /// Parse the rules from an A2S_RULES payload, starting at the header byte.
+            pub fn from_payload(payload: &[u8]) -> Result<Self, QueryError> {
+                Self::from_payload_with(&ParseOptions::default(), payload)
+            }
+
+            /// Like [`DayZRules::from_payload`], decoding strings and limiting their length and
+            /// the number of rules, binary chunks included, with `options`.
+            pub fn from_payload_with(
+                options: &ParseOptions,
+                payload: &[u8],
+            ) -> Result<Self, QueryError> {
+                let mut reader = options.reader(payload);
+                let header = reader.read_u8()?;
+                let expected = ResponseKind::Rules.as_byte();
+                if header != expected {
+                    return Err(QueryError::UnexpectedHeader {
+                        expected,
+                        found: header,
+                    });
+                }
+                let _rule_count = reader.read_i16()?;
+
+                let mut rules = Rules::default();
+                let mut chunks: Vec<(Byte, &[u8])> = Vec::new();
+                let mut parsed = 0;
+                while reader.remaining() > 0 {
+                    if parsed == options.max_rules {
+                        return Err(QueryError::TooManyEntries {
+                            limit: options.max_rules,
+                        });
+                    }
+                    parsed += 1;
+                    let key_position = reader.position();
+                    let key = reader.read_cstring_bytes()?;
+                    let value_position = reader.position();
+                    let value = reader.read_cstring_bytes()?;
+                    match chunk_index(key) {
+                        Some(index) => chunks.push((index, value)),
+                        None => rules.push(
+                            decode(&reader, key, key_position)?,
+                            decode(&reader, value, value_position)?,
+                        ),
+                    }
+                }
+
+                chunks.sort_by_key(|(index, _)| *index);
+                let data = unescape(chunks.into_iter().flat_map(|(_, chunk)| chunk));
+                let mut dayz_rules = Self::from_binary(options.reader(&data))?;
+                dayz_rules.rules = rules;
+                Ok(dayz_rules)
+            }
+
+            /// Parse the unescaped binary data of the chunked rules.
+            fn from_binary(mut reader: PacketReader) -> Result<Self, QueryError> {
+                let version = reader.read_u8()?;
+                let overflow = reader.read_u8()?;
+                let dlc_flags = reader.read_i16()? as u16;
+                let difficulty = reader.read_u8()?;
+                let crosshair = reader.read_u8()?;
+
+                let dlc_hashes = (0..dlc_flags.count_ones())
+                    .map(|_| reader.read_i32().map(|hash| hash as u32))
+                    .collect::<Result<Vec<u32>, QueryError>>()?;
+
+                let mod_count = reader.read_u8()?;
+                let mut mods = Vec::with_capacity(mod_count as usize);
+                for _ in 0..mod_count {
+                    let hash = reader.read_i32()? as u32;
+                    let id_info = reader.read_u8()?;
+                    let id_length = (id_info & 0x0F) as usize;
+                    if id_length > 8 {
+                        return Err(QueryError::MalformedPacket(format!(
+                            "mod ID is {id_length} bytes long"
+                        )));
+                    }
+                    let mut id = [0; 8];
+                    id[..id_length].copy_from_slice(reader.read_bytes(id_length)?);
+                    let name = read_short_string(&mut reader)?;
+
+                    mods.push(DayZMod {
+                        hash,
+                        workshop_id: u64::from_le_bytes(id),
+                        is_dlc: id_info & DLC_FLAG != 0,
+                        name,
+                    });
+                }
+
+                let signature_count = reader.read_u8()?;
+                let mut signatures = Vec::with_capacity(signature_count as usize);
+                for _ in 0..signature_count {
+                    signatures.push(read_short_string(&mut reader)?);
+                }
+
+                Ok(Self {
+                    version,
+                    overflow,
+                    dlc_flags,
+                    difficulty,
+                    crosshair,
+                    dlc_hashes,
+                    mods,
+                    signatures,
+                    rules: Rules::default(),
+                })
+            }
+        }
+
+        /// Undo the escaping of the chunked rule values: `01 01` is `01`, `01 02` is `00` and
+        /// `01 03` is `FF`.
+        fn unescape<'a, I>(bytes: I) -> Vec<u8>
+        where
+            I: IntoIterator<Item = &'a u8>,
+        {
+            let mut bytes = bytes.into_iter();
+            let mut data = Vec::new();
+            while let Some(&byte) = bytes.next() {
+                if byte != 0x01 {
+                    data.push(byte);
+                    continue;
+                }
+                match bytes.next() {
+                    Some(0x01) => data.push(0x01),
+                    Some(0x02) => data.push(0x00),
+                    Some(0x03) => data.push(0xFF),
+                    Some(&other) => data.extend([0x01, other]),
+                    None => data.push(0x01),
+                }
+            }
+            data
+        }
+
+        /// Decode a string with the encoding of the reader, given its position.
+        fn decode(
+            reader: &PacketReader,
+            bytes: &[u8],
+            position: usize,
+        ) -> Result<String, QueryError> {
+            reader
+                .encoding()
+                .decode(bytes)
+                .ok_or(QueryError::InvalidUtf8 { position })
+        }
+
+        /// Read a string prefixed by its length in a byte.
+        fn read_short_string(reader: &mut PacketReader) -> Result<String, QueryError> {
+            let length = reader.read_u8()? as usize;
+            let position = reader.position();
+            let bytes = reader.read_bytes(length)?;
+            decode(reader, bytes, position)
+        }
+
+        /// Index of the chunk of binary data a rule key marks, if it is one.
+        ///
+        /// Chunk keys are two raw bytes, the 1-based index of the chunk and the number of
+        /// chunks, which keeps them apart from the printable keys of the plain rules.
+        fn chunk_index(key: &[u8]) -> Option<Byte> {
+            match *key {
+                [index, count]
+                    if !index.is_ascii_graphic()
+                        && !count.is_ascii_graphic()
+                        && (1..=count).contains(&index) =>
+                {
+                    Some(index)
+                }
+                _ => None,
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+
+            /// A2S_RULES payload of a DayZ server running Community Framework, Dabs Framework and
+            /// the Livonia DLC, from the header byte on.
+            const DAYZ_RULES: &[u8] = include_bytes!("../tests/fixtures/dayz_rules.bin");
+
+            #[test]
+            fn test_dayz_rules_from_payload() {
+                let dayz_rules = DayZRules::from_payload(DAYZ_RULES).unwrap();
+
+                assert_eq!(dayz_rules.version, 3);
+                assert_eq!(dayz_rules.overflow, 0);
+                assert_eq!(dayz_rules.dlc_flags, 0x0001);
+                assert_eq!(dayz_rules.difficulty, 0x2A);
+                assert_eq!(dayz_rules.crosshair, 1);
+                assert_eq!(dayz_rules.dlc_hashes, vec![0x7E6E_01FF]);
+                assert_eq!(
+                    dayz_rules.mods,
+                    vec![
+                        DayZMod {
+                            hash: 0x5C3F_0A11,
+                            workshop_id: 1_559_212_036,
+                            is_dlc: false,
+                            name: "Community Framework".to_string(),
+                        },
+                        DayZMod {
+                            hash: 0x01A2_B3C4,
+                            workshop_id: 2_545_327_648,
+                            is_dlc: false,
+                            name: "Dabs Framework".to_string(),
+                        },
+                        DayZMod {
+                            hash: 0xD00F_1E00,
+                            workshop_id: 1_042_220,
+                            is_dlc: true,
+                            name: "Livonia".to_string(),
+                        },
+                    ]
+                );
+                assert_eq!(dayz_rules.signatures, vec!["dayz", "cf", "dabs"]);
+                assert_eq!(dayz_rules.rules.len(), 8);
+                assert_eq!(dayz_rules.rules["island"], "chernarusplus");
+                assert_eq!(dayz_rules.rules["requiredVersion"], "125");
+            }
+
+            #[test]
+            fn test_dayz_rules_chunks_out_of_order() {
+                // The first chunk of the fixture, key and value, moved after the other rules.
+                let value_start = 3 + 3;
+                let chunk_end = value_start
+                    + DAYZ_RULES[value_start..]
+                        .iter()
+                        .position(|byte| *byte == 0)
+                        .unwrap()
+                    + 1;
+                let first_chunk = 3..chunk_end;
+                let mut payload = DAYZ_RULES[..3].to_vec();
+                payload.extend(&DAYZ_RULES[first_chunk.end..]);
+                payload.extend(&DAYZ_RULES[first_chunk]);
+
+                assert_eq!(
+                    DayZRules::from_payload(&payload).unwrap(),
+                    DayZRules::from_payload(DAYZ_RULES).unwrap()
+                );
+            }
+
+            #[test]
+            fn test_dayz_rules_two_byte_key() {
+                let mut payload = vec![0x45];
+                payload.extend(2i16.to_le_bytes());
+                payload.extend(b"ai\x001\x00");
+                payload.extend([0x01, 0x01, 0x00]);
+                payload.extend([3, 0x01, 0x02, 0x01, 0x02, 0x01, 0x02, 0x01, 0x02]);
+                payload.extend([0x01, 0x02, 0x01, 0x02, 0x01, 0x02, 0x00]);
+
+                let dayz_rules = DayZRules::from_payload(&payload).unwrap();
+                assert_eq!(dayz_rules.version, 3);
+                assert_eq!(dayz_rules.rules.len(), 1);
+                assert_eq!(dayz_rules.rules["ai"], "1");
+            }
+
+            #[test]
+            fn test_dayz_rules_options() {
+                // 8 plain rules and 3 chunks
+                let options = ParseOptions {
+                    max_rules: 11,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    DayZRules::from_payload_with(&options, DAYZ_RULES).unwrap(),
+                    DayZRules::from_payload(DAYZ_RULES).unwrap()
+                );
+                let options = ParseOptions {
+                    max_rules: 10,
+                    ..Default::default()
+                };
+                assert!(matches!(
+                    DayZRules::from_payload_with(&options, DAYZ_RULES),
+                    Err(QueryError::TooManyEntries { limit: 10 })
+                ));
+
+                let options = ParseOptions {
+                    max_string_len: 8,
+                    ..Default::default()
+                };
+                assert!(matches!(
+                    DayZRules::from_payload_with(&options, DAYZ_RULES),
+                    Err(QueryError::StringTooLong { limit: 8 })
+                ));
+
+                let mut payload = DAYZ_RULES.to_vec();
+                let value = payload.len() - 3;
+                payload[value] = 0xFF;
+                let options = ParseOptions {
+                    encoding: crate::Encoding::Utf8Strict,
+                    ..Default::default()
+                };
+                assert!(matches!(
+                    DayZRules::from_payload_with(&options, &payload),
+                    Err(QueryError::InvalidUtf8 { position }) if position == value
+                ));
+                let dayz_rules = DayZRules::from_payload(&payload).unwrap();
+                assert_eq!(dayz_rules.rules["timeLeft"], "\u{FFFD}5");
+            }
+
+            #[test]
+            fn test_dayz_rules_truncated() {
+                let mut payload = vec![0x45];
+                payload.extend(1i16.to_le_bytes());
+                payload.extend([0x01, 0x01, 0x00, 3, 0x00]);
+
+                assert!(matches!(
+                    DayZRules::from_payload(&payload),
+                    Err(QueryError::UnexpectedEnd { .. })
+                ));
+            }
+
+            #[test]
+            fn test_unescape() {
+                assert_eq!(
+                    unescape(&[0x05, 0x01, 0x01, 0x01, 0x02, 0x01, 0x03]),
+                    vec![0x05, 0x01, 0x00, 0xFF]
+                );
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// A2S_PLAYER record: index, name, score and duration.
+        fn player_bytes(index: u8, name: &[u8], score: i32, duration: f32) -> Vec<u8> {
+            let mut bytes = vec![index];
+            bytes.extend(name);
+            bytes.push(0);
+            bytes.extend(score.to_le_bytes());
+            bytes.extend(duration.to_le_bytes());
+            bytes
+        }
+
+        #[test]
+        fn test_player_from_bytes() {
+            let player = Player::from_bytes(&player_bytes(4, b"Alice", 12, 61.5)).unwrap();
+            assert_eq!(player.index(), 4);
+            assert_eq!(player.name(), "Alice");
+            assert_eq!(player.score(), 12);
+            assert_eq!(player.duration(), 61.5);
+        }
+
+        #[test]
+        fn test_player_name_without_null_terminator() {
+            let mut bytes = vec![0];
+            bytes.extend(b"Runaway name with no terminator");
+
+            assert!(matches!(
+                Player::from_bytes(&bytes),
+                Err(QueryError::UnterminatedString)
+            ));
+            assert!(matches!(
+                Player::get_players(&bytes),
+                Err(QueryError::UnterminatedString)
+            ));
+        }
+
+        #[test]
+        fn test_player_name_bytes() {
+            // UTF-8 name with an engine color code
+            let name = b"\x07Zo\xC3\xAB";
+            let player = Player::from_bytes(&player_bytes(1, name, 0, 1.0)).unwrap();
+            assert_eq!(player.name_bytes(), name);
+            assert_eq!(player.name(), "\x07Zoë");
+
+            let bytes = player_bytes(1, name, 0, 1.0);
+            let player = Player::from_iter_bytes(&mut bytes.iter()).unwrap();
+            assert_eq!(player.name_bytes(), name);
+        }
+
+        #[test]
+        fn test_player_from_iter_bytes_truncated() {
+            let bytes = player_bytes(1, b"Player", 5, 1.0);
+            assert!(matches!(
+                Player::from_iter_bytes(&mut bytes[..10].iter()),
+                Err(QueryError::UnexpectedEnd {
+                    position: 8,
+                    needed: 4
+                })
+            ));
+            assert!(matches!(
+                Player::from_iter_bytes(&mut bytes[..0].iter()),
+                Err(QueryError::UnexpectedEnd {
+                    position: 0,
+                    needed: 1
+                })
+            ));
+
+            let mut bytes = player_bytes(1, b"Player", 5, 1.0);
+            bytes.extend(3i32.to_le_bytes());
+            assert!(matches!(
+                Player::from_iter_bytes_the_ship(&mut bytes.iter()),
+                Err(QueryError::UnexpectedEnd {
+                    position: 20,
+                    needed: 4
+                })
+            ));
+            bytes.extend(500i32.to_le_bytes());
+            let player = Player::from_iter_bytes_the_ship(&mut bytes.iter()).unwrap();
+            assert_eq!((player.deaths(), player.money()), (Some(3), Some(500)));
+        }
+
+        #[test]
+        fn test_player_name_utf8() {
+            let name = "Привет";
+            let bytes = player_bytes(0, name.as_bytes(), 5, 1.0);
+            assert_eq!(Player::from_bytes(&bytes).unwrap().name(), name);
+            assert_eq!(
+                Player::from_iter_bytes(&mut bytes.iter()).unwrap().name(),
+                name
+            );
+        }
+
+        #[test]
+        fn test_players_to_payload_round_trip() {
+            for count in [0, 1, 100] {
+                let players: Vec<Player> = (0..count)
+                    .map(|i| Player::new(i as u8, &format!("Player with a long name #{i}"), i, 1.5))
+                    .collect();
+
+                let payload = players_to_payload(&players);
+                assert_eq!(payload[..2], [0x44, count as u8]);
+                assert_eq!(Player::get_players(&payload[2..]).unwrap(), players);
+                if count == 100 {
+                    assert!(payload.len() > 1400);
+                }
+            }
+
+            let player = Player::new(3, "Zoë", -2, 61.5).with_the_ship(4, 1500);
+            let payload = players_to_payload(std::slice::from_ref(&player));
+            assert_eq!(
+                Player::get_players_the_ship(&payload[2..]).unwrap(),
+                vec![player.clone()]
+            );
+            assert_eq!(
+                Player::from_bytes(&player.to_bytes()).unwrap().name(),
+                "Zoë"
+            );
+
+            let players = vec![Player::new(0, "A", 0, 0.0); 300];
+            assert_eq!(players_to_payload(&players)[1], 255);
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_snapshot_json() {
+            let snapshot = ServerSnapshot {
+                info: Info::builder().name("test").build(),
+                players: vec![
+                    Player::new(0, "Alice", 12, 61.5),
+                    Player::new(1, "Bob", -2, 0.25).with_the_ship(4, 1500),
+                ],
+                rules: Some(Rules::from(vec![(
+                    "sv_cheats".to_string(),
+                    "0".to_string(),
+                )])),
+            };
+
+            let json = serde_json::to_value(&snapshot).unwrap();
+            assert_eq!(json["info"]["name"], "test");
+            assert_eq!(
+                json["players"],
+                serde_json::json!([
+                    {
+                        "index": 0,
+                        "name": "Alice",
+                        "score": 12,
+                        "duration": 61.5,
+                    },
+                    {
+                        "index": 1,
+                        "name": "Bob",
+                        "score": -2,
+                        "duration": 0.25,
+                        "deaths": 4,
+                        "money": 1500,
+                    },
+                ])
+            );
+            assert_eq!(json["rules"], serde_json::json!({ "sv_cheats": "0" }));
+            assert_eq!(
+                serde_json::from_value::<ServerSnapshot>(json).unwrap(),
+                snapshot
+            );
+
+            // A name that did not decode to its bytes keeps them
+            let mut bytes = vec![2];
+            bytes.extend(b"\xFFa\0");
+            bytes.extend([0; 8]);
+            let (player, _) = Player::parse(&bytes).unwrap();
+            let json = serde_json::to_value(&player).unwrap();
+            assert_eq!(json["name_bytes"], serde_json::json!([0xFF, b'a']));
+            assert_eq!(serde_json::from_value::<Player>(json).unwrap(), player);
+
+            // Duplicate rules are left out
+            let rules = Rules::from(vec![
+                ("sv_tags".to_string(), "pve".to_string()),
+                ("sv_tags".to_string(), "pvp".to_string()),
+            ]);
+            let json = serde_json::to_value(&rules).unwrap();
+            assert_eq!(json, serde_json::json!({ "sv_tags": "pvp" }));
+            assert_eq!(serde_json::from_value::<Rules>(json).unwrap().len(), 1);
+        }
+
+        #[cfg(feature = "postcard")]
+        #[test]
+        fn test_snapshot_round_trip() {
+            let mut info = vec![0x49, 0x11];
+            info.extend(b"Test Server\0de_dust2\0cstrike\0Counter-Strike\0");
+            info.extend(730i16.to_le_bytes());
+            info.extend([5, 24, 0, b'd', b'l', 0x00, 0x01]);
+            info.extend(b"1.0.0.0\0");
+            // Every extra data field, then bytes this crate does not know
+            info.push(0x80 | 0x10 | 0x40 | 0x20 | 0x01);
+            info.extend(27015i16.to_le_bytes());
+            info.extend(76561198000000000u64.to_le_bytes());
+            info.extend(27020i16.to_le_bytes());
+            info.extend(b"SourceTV\0secure,dust\0");
+            info.extend(730u64.to_le_bytes());
+            info.extend([0x00, 0x2A]);
+
+            let snapshot = ServerSnapshot {
+                info: Info::from_bytes(&info).unwrap(),
+                players: vec![
+                    Player::new(0, "Alice", 12, 61.5),
+                    Player::new(1, "Zoë", -2, 0.25).with_the_ship(4, 1500),
+                ],
+                rules: Some(Rules::from(vec![
+                    ("sv_cheats".to_string(), "0".to_string()),
+                    ("mp_timelimit".to_string(), "20".to_string()),
+                    ("sv_cheats".to_string(), "1".to_string()),
+                ])),
+            };
+            let bytes = snapshot.to_bytes();
+            assert_eq!(ServerSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+
+            let snapshot = ServerSnapshot {
+                rules: None,
+                ..snapshot
+            };
+            assert_eq!(
+                ServerSnapshot::from_bytes(&snapshot.to_bytes()).unwrap(),
+                snapshot
+            );
+            assert!(ServerSnapshot::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+
+            // GoldSource Mac server, whose environment byte is sent in uppercase
+            let mut info = vec![0x6D];
+            info.extend(b"10.0.0.1:27015\0Server\0de_dust\0cstrike\0CS\0");
+            info.extend([3, 32, 47, b'D', b'M', 0x00, 0x00, 0x01, 0]);
+            let snapshot = ServerSnapshot {
+                info: Info::from_bytes(&info).unwrap(),
+                players: Vec::new(),
+                rules: None,
+            };
+            assert_eq!(
+                ServerSnapshot::from_bytes(&snapshot.to_bytes()).unwrap(),
+                snapshot
+            );
+        }
+
+        #[test]
+        fn test_player_parse_consumed() {
+            let first = Player::new(0, "Alice", 3, 12.5);
+            let second = Player::new(1, "", -1, 0.0);
+            let mut bytes = first.to_bytes();
+            bytes.extend(second.to_bytes());
+
+            // Index, "Alice" and its terminator, score, duration
+            let (player, consumed) = Player::parse(&bytes).unwrap();
+            assert_eq!((player, consumed), (first, 1 + 6 + 4 + 4));
+            let (player, consumed) = Player::parse(&bytes[15..]).unwrap();
+            assert_eq!((player, consumed), (second, 1 + 1 + 4 + 4));
+        }
+
+        #[test]
+        fn test_player_iter_from_bytes() {
+            let mut bytes = player_bytes(0, b"Alice", 12, 61.5);
+            bytes.extend(player_bytes(1, b"Bob", 3, 10.0));
+
+            let mut players = Player::iter_from_bytes(&bytes);
+            assert_eq!(players.next().unwrap().unwrap().name(), "Alice");
+            assert_eq!(players.next().unwrap().unwrap().name(), "Bob");
+            assert!(players.next().is_none());
+            drop(players);
+
+            bytes.extend([2]);
+            bytes.extend(b"Runaway name with no terminator");
+            let results: Vec<_> = Player::iter_from_bytes(&bytes).collect();
+            assert_eq!(results.len(), 3);
+            assert!(matches!(results[2], Err(QueryError::UnterminatedString)));
+
+            assert_eq!(Player::iter_from_bytes(&[]).count(), 0);
+        }
+
+        #[test]
+        fn test_player_connected_duration() {
+            let player = Player::from_bytes(&player_bytes(0, b"Alice", 12, 61.5)).unwrap();
+            assert_eq!(player.connected_duration(), Duration::from_millis(61_500));
+
+            for duration in [-1.0, f32::NAN, f32::INFINITY] {
+                let player = Player::from_bytes(&player_bytes(0, b"Bob", 0, duration)).unwrap();
+                assert_eq!(player.connected_duration(), Duration::ZERO);
+            }
+        }
+
+        #[test]
+        fn test_player_cmp_by_score() {
+            let mut players: Vec<Player> = [("Carol", 7), ("Alice", 12), ("Bob", 7), ("Dave", 0)]
+                .iter()
+                .map(|(name, score)| {
+                    Player::from_bytes(&player_bytes(0, name.as_bytes(), *score, 1.0)).unwrap()
+                })
+                .collect();
+
+            players.sort_by(|a, b| a.cmp_by_score(b));
+            let names: Vec<&str> = players.iter().map(Player::name).collect();
+            assert_eq!(names, vec!["Alice", "Bob", "Carol", "Dave"]);
+        }
+
+        #[test]
+        fn test_get_players_the_ship() {
+            // (name, score, duration, deaths, money)
+            let table: [(&str, i32, f32, i32, i32); 3] = [
+                ("Alice", 4, 120.0, 2, 1500),
+                ("Bob", 0, 30.5, 5, 0),
+                ("Carol", 9, 600.25, 0, 23_000),
+            ];
+
+            let mut bytes = Vec::new();
+            for (index, (name, score, duration, deaths, money)) in table.iter().enumerate() {
+                bytes.extend(player_bytes(
+                    index as u8,
+                    name.as_bytes(),
+                    *score,
+                    *duration,
+                ));
+                bytes.extend(deaths.to_le_bytes());
+                bytes.extend(money.to_le_bytes());
+            }
+
+            let players = Player::get_players_the_ship(&bytes).unwrap();
+            assert_eq!(players.len(), table.len());
+            for (player, (name, score, duration, deaths, money)) in players.iter().zip(table) {
+                assert_eq!(player.name(), name);
+                assert_eq!(player.score(), score);
+                assert_eq!(player.duration(), duration);
+                assert_eq!(player.deaths(), Some(deaths));
+                assert_eq!(player.money(), Some(money));
+            }
+
+            let player = Player::from_bytes(&player_bytes(0, b"Alice", 12, 61.5)).unwrap();
+            assert_eq!(player.deaths(), None);
+            assert_eq!(player.money(), None);
+        }
+    }
+}
+
+pub mod transport {
+
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::io;
+    #[cfg(feature = "socks5")]
+    use std::io::{Read, Write};
+    #[cfg(feature = "socks5")]
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpStream};
+    use std::net::{SocketAddr, UdpSocket};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Datagram transport a [`Server`](crate::Server) sends its requests over.
+    ///
+    /// Implemented for [`UdpSocket`], and by [`MockTransport`] to query canned responses
+    /// without a live server. Implementations follow the semantics of [`UdpSocket`]: a receive
+    /// that times out, or finds nothing queued in non-blocking mode, fails with `WouldBlock`
+    /// or `TimedOut`.
+    pub trait Transport: fmt::Debug + Send + Sync {
+        fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize>;
+        fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
+        fn read_timeout(&self) -> io::Result<Option<Duration>>;
+        fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()>;
+        fn write_timeout(&self) -> io::Result<Option<Duration>>;
+        fn set_write_timeout(&self, duration: Option<Duration>) -> io::Result<()>;
+        fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+    }
+
+    impl Transport for UdpSocket {
+        fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+            UdpSocket::send_to(self, buffer, addr)
+        }
+        fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            UdpSocket::recv_from(self, buffer)
+        }
+        fn read_timeout(&self) -> io::Result<Option<Duration>> {
+            UdpSocket::read_timeout(self)
+        }
+        fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            UdpSocket::set_read_timeout(self, duration)
+        }
+        fn write_timeout(&self) -> io::Result<Option<Duration>> {
+            UdpSocket::write_timeout(self)
+        }
+        fn set_write_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            UdpSocket::set_write_timeout(self, duration)
+        }
+        fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+            UdpSocket::set_nonblocking(self, nonblocking)
+        }
+    }
+
+    /// Shares a transport, such as a [`MockTransport`] whose requests are inspected after
+    /// handing it to a [`Server`](crate::Server).
+    impl<T: Transport + ?Sized> Transport for Arc<T> {
+        fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+            (**self).send_to(buffer, addr)
+        }
+        fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            (**self).recv_from(buffer)
+        }
+        fn read_timeout(&self) -> io::Result<Option<Duration>> {
+            (**self).read_timeout()
+        }
+        fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            (**self).set_read_timeout(duration)
+        }
+        fn write_timeout(&self) -> io::Result<Option<Duration>> {
+            (**self).write_timeout()
+        }
+        fn set_write_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            (**self).set_write_timeout(duration)
+        }
+        fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+            (**self).set_nonblocking(nonblocking)
+        }
+    }
+
+    /// In-memory transport answering each request with canned datagrams.
+    ///
+    /// The first request sent is answered with the first set of datagrams, the second with the
+    /// second, and so on; requests beyond them go unanswered. A receive with nothing queued
+    /// fails immediately, as if the read timeout had passed, so tests run without waiting.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use valve_server_query::{MockTransport, Server};
+    ///
+    /// let transport = Arc::new(MockTransport::new(vec![vec![vec![
+    ///     0xFF, 0xFF, 0xFF, 0xFF, 0x6A, 0x00,
+    /// ]]]));
+    /// let server = Server::with_transport("127.0.0.1:27015".parse().unwrap(), transport.clone());
+    ///
+    /// assert!(server.ping_packet().is_ok());
+    /// assert_eq!(transport.requests(), vec![vec![0xFF, 0xFF, 0xFF, 0xFF, 0x69]]);
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct MockTransport {
+        state: Mutex<MockState>,
+    }
+
+    #[derive(Debug, Default)]
+    struct MockState {
+        /// Datagrams to answer each upcoming request with.
+        responses: VecDeque<Vec<Vec<u8>>>,
+        /// Datagrams waiting to be received, with the address they come from.
+        queued: VecDeque<(Vec<u8>, SocketAddr)>,
+        requests: Vec<Vec<u8>>,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    }
+
+    impl MockTransport {
+        pub fn new(responses: Vec<Vec<Vec<u8>>>) -> Self {
+            Self {
+                state: Mutex::new(MockState {
+                    responses: responses.into(),
+                    ..Default::default()
+                }),
+            }
+        }
+
+        /// Requests sent so far, in order.
+        pub fn requests(&self) -> Vec<Vec<u8>> {
+            self.lock().requests.clone()
+        }
+
+        fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+            self.state.lock().unwrap_or_else(|e| e.into_inner())
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+            let mut state = self.lock();
+            state.requests.push(buffer.to_vec());
+            let datagrams = state.responses.pop_front().unwrap_or_default();
+            state
+                .queued
+                .extend(datagrams.into_iter().map(|datagram| (datagram, addr)));
+            Ok(buffer.len())
+        }
+        fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            let (datagram, source) = self
+                .lock()
+                .queued
+                .pop_front()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::WouldBlock))?;
+            // Like a socket, discard what does not fit
+            let size = datagram.len().min(buffer.len());
+            buffer[..size].copy_from_slice(&datagram[..size]);
+            Ok((size, source))
+        }
+        fn read_timeout(&self) -> io::Result<Option<Duration>> {
+            Ok(self.lock().read_timeout)
+        }
+        fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            self.lock().read_timeout = duration;
+            Ok(())
+        }
+        fn write_timeout(&self) -> io::Result<Option<Duration>> {
+            Ok(self.lock().write_timeout)
+        }
+        fn set_write_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            self.lock().write_timeout = duration;
+            Ok(())
+        }
+        fn set_nonblocking(&self, _nonblocking: bool) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Transport tunnelling datagrams through a SOCKS5 proxy, associated with a UDP ASSOCIATE
+    /// request (RFC 1928).
+    ///
+    /// Only proxies accepting clients without authentication are supported. The association
+    /// lasts as long as the transport, which keeps open the TCP connection it was requested
+    /// over. Fragmented datagrams are not supported, and are discarded.
+    #[cfg(feature = "socks5")]
+    #[derive(Debug)]
+    pub struct Socks5Transport {
+        /// Connection the association was requested over, which ends it when closed.
+        _control: TcpStream,
+        socket: UdpSocket,
+        /// Address the proxy relays datagrams from.
+        relay: SocketAddr,
+    }
+
+    #[cfg(feature = "socks5")]
+    impl Socks5Transport {
+        /// Length of the longest header the proxy puts before each datagram, with an IPv6
+        /// address.
+        const MAX_HEADER_LEN: usize = 3 + 1 + 16 + 2;
+
+        /// Associate with the SOCKS5 proxy at `proxy`, waiting at most `timeout` for each step
+        /// of the handshake.
+        pub fn connect(proxy: SocketAddr, timeout: Duration) -> io::Result<Self> {
+            let mut control = TcpStream::connect_timeout(&proxy, timeout)?;
+            control.set_read_timeout(Some(timeout))?;
+            control.set_write_timeout(Some(timeout))?;
+
+            // Version 5, offering no authentication
+            control.write_all(&[0x05, 0x01, 0x00])?;
+            let mut method = [0; 2];
+            control.read_exact(&mut method)?;
+            if method != [0x05, 0x00] {
+                return Err(io::Error::other("SOCKS5 proxy requires authentication"));
+            }
+
+            let unspecified = match proxy {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            let socket = UdpSocket::bind((unspecified, 0))?;
+
+            // UDP ASSOCIATE, from the port datagrams will be sent from
+            let mut request = vec![0x05, 0x03, 0x00];
+            put_socks_address(&mut request, socket.local_addr()?);
+            control.write_all(&request)?;
+            let mut reply = [0; 4];
+            control.read_exact(&mut reply)?;
+            if reply[..2] != [0x05, 0x00] {
+                return Err(io::Error::other(format!(
+                    "SOCKS5 proxy refused the association with reply {:#04X}",
+                    reply[1]
+                )));
+            }
+            let mut address = vec![reply[3]];
+            address.resize(
+                match reply[3] {
+                    0x01 => 1 + 4 + 2,
+                    0x04 => 1 + 16 + 2,
+                    _ => return Err(io::Error::other("SOCKS5 proxy sent an unsupported address")),
+                },
+                0,
+            );
+            control.read_exact(&mut address[1..])?;
+            let (mut relay, _) = get_socks_address(&address).expect("address has its length");
+            // An unspecified address stands for the address of the proxy itself
+            if relay.ip().is_unspecified() {
+                relay.set_ip(proxy.ip());
+            }
+
+            Ok(Self {
+                _control: control,
+                socket,
+                relay,
+            })
+        }
+
+        /// Address the proxy relays datagrams from.
+        pub fn relay(&self) -> SocketAddr {
+            self.relay
+        }
+    }
+
+    #[cfg(feature = "socks5")]
+    impl Transport for Socks5Transport {
+        fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+            // Reserved, and fragment number 0 for a whole datagram
+            let mut packet = vec![0x00, 0x00, 0x00];
+            put_socks_address(&mut packet, addr);
+            packet.extend(buffer);
+            self.socket.send_to(&packet, self.relay)?;
+            Ok(buffer.len())
+        }
+        fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            let mut packet = vec![0; buffer.len() + Self::MAX_HEADER_LEN];
+            loop {
+                let (size, source) = self.socket.recv_from(&mut packet)?;
+                let packet = &packet[..size];
+                if source != self.relay || packet.len() < 4 || packet[2] != 0x00 {
+                    continue;
+                }
+                let Some((source, length)) = get_socks_address(&packet[3..]) else {
+                    continue;
+                };
+                let datagram = &packet[3 + length..];
+                // Like a socket, discard what does not fit
+                let size = datagram.len().min(buffer.len());
+                buffer[..size].copy_from_slice(&datagram[..size]);
+                return Ok((size, source));
+            }
+        }
+        fn read_timeout(&self) -> io::Result<Option<Duration>> {
+            self.socket.read_timeout()
+        }
+        fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            self.socket.set_read_timeout(duration)
+        }
+        fn write_timeout(&self) -> io::Result<Option<Duration>> {
+            self.socket.write_timeout()
+        }
+        fn set_write_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+            self.socket.set_write_timeout(duration)
+        }
+        fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+            self.socket.set_nonblocking(nonblocking)
+        }
+    }
+
+    /// Write `addr` as a SOCKS5 address: its type, the IP address, then the port in network
+    /// byte order.
+    #[cfg(feature = "socks5")]
+    fn put_socks_address(bytes: &mut Vec<u8>, addr: SocketAddr) {
+        match addr.ip() {
+            IpAddr::V4(ip) => {
+                bytes.push(0x01);
+                bytes.extend(ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                bytes.push(0x04);
+                bytes.extend(ip.octets());
+            }
+        }
+        bytes.extend(addr.port().to_be_bytes());
+    }
+
+    /// Read a SOCKS5 address, returning it with its length in bytes. Domain names and unknown
+    /// address types give `None`.
+    #[cfg(feature = "socks5")]
+    fn get_socks_address(bytes: &[u8]) -> Option<(SocketAddr, usize)> {
+        let (ip, length) = match *bytes.first()? {
+            0x01 => {
+                let octets: [u8; 4] = bytes.get(1..5)?.try_into().ok()?;
+                (IpAddr::from(octets), 1 + 4)
+            }
+            0x04 => {
+                let octets: [u8; 16] = bytes.get(1..17)?.try_into().ok()?;
+                (IpAddr::from(octets), 1 + 16)
+            }
+            _ => return None,
+        };
+        let port = u16::from_be_bytes(bytes.get(length..length + 2)?.try_into().ok()?);
+        Some((SocketAddr::new(ip, port), length + 2))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::models::{players_to_payload, Player};
+        use crate::server::tests::{challenge_response, split_packet};
+        use crate::types::{put_cstring, put_short};
+        use crate::{QueryMeta, Server};
+
+        /// A2S_INFO response of the example on the Valve developer wiki.
+        #[rustfmt::skip]
+        const INFO_RESPONSE: [u8; 100] = [
+            0xFF, 0xFF, 0xFF, 0xFF, 0x49, 0x02, 0x67, 0x61, 0x6D, 0x65, 0x32, 0x78, 0x73, 0x2E, 0x63, 0x6F,
+            0x6D, 0x20, 0x43, 0x6F, 0x75, 0x6E, 0x74, 0x65, 0x72, 0x2D, 0x53, 0x74, 0x72, 0x69, 0x6B, 0x65,
+            0x20, 0x53, 0x6F, 0x75, 0x72, 0x63, 0x65, 0x20, 0x23, 0x31, 0x00, 0x64, 0x65, 0x5F, 0x64, 0x75,
+            0x73, 0x74, 0x00, 0x63, 0x73, 0x74, 0x72, 0x69, 0x6B, 0x65, 0x00, 0x43, 0x6F, 0x75, 0x6E, 0x74,
+            0x65, 0x72, 0x2D, 0x53, 0x74, 0x72, 0x69, 0x6B, 0x65, 0x3A, 0x20, 0x53, 0x6F, 0x75, 0x72, 0x63,
+            0x65, 0x00, 0xF0, 0x00, 0x05, 0x10, 0x04, 0x64, 0x6C, 0x00, 0x00, 0x31, 0x2E, 0x30, 0x2E, 0x30,
+            0x2E, 0x32, 0x32, 0x00,
+        ];
+        /// Challenge response of the example on the Valve developer wiki.
+        const CHALLENGE_RESPONSE: [u8; 9] = [0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x4B, 0xA1, 0xD5, 0x22];
+        /// A2S_PLAYER response of the example on the Valve developer wiki.
+        #[rustfmt::skip]
+        const PLAYERS_RESPONSE: [u8; 54] = [
+            0xFF, 0xFF, 0xFF, 0xFF, 0x44, 0x02, 0x01, 0x5B, 0x44, 0x5D, 0x2D, 0x2D, 0x2D, 0x2D, 0x3E, 0x54,
+            0x2E, 0x4E, 0x2E, 0x57, 0x3C, 0x2D, 0x2D, 0x2D, 0x2D, 0x00, 0x0E, 0x00, 0x00, 0x00, 0xB4, 0x97,
+            0x00, 0x44, 0x02, 0x4B, 0x69, 0x6C, 0x6C, 0x65, 0x72, 0x20, 0x21, 0x21, 0x21, 0x00, 0x05, 0x00,
+            0x00, 0x00, 0x69, 0x24, 0xD9, 0x43,
+        ];
+        /// Address the mock transport answers from.
+        const SERVER_ADDR: &str = "127.0.0.1:27015";
+
+        #[test]
+        fn test_mock_info() {
+            let transport = Arc::new(MockTransport::new(vec![vec![INFO_RESPONSE.to_vec()]]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+
+            let info = server.info().unwrap();
+            assert_eq!(info.name(), "game2xs.com Counter-Strike Source #1");
+            assert_eq!(info.map(), "de_dust");
+            assert_eq!(info.folder(), "cstrike");
+            assert_eq!(info.game(), "Counter-Strike: Source");
+            assert_eq!(*info.steam_app_id(), 240);
+            assert_eq!(
+                (*info.player_count(), *info.player_max(), *info.bot_count()),
+                (5, 16, 4)
+            );
+            assert_eq!(info.game_version(), "1.0.0.22");
+            assert_eq!(transport.requests().len(), 1);
+
+            let transport = Arc::new(MockTransport::new(vec![vec![INFO_RESPONSE.to_vec()]]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+            let (_info, meta) = server.info_with_meta().unwrap();
+            assert_eq!(
+                meta,
+                QueryMeta {
+                    fragments: 1,
+                    compressed: false,
+                    largest_packet: INFO_RESPONSE.len(),
+                    challenges: 0,
+                }
+            );
+        }
+
+        #[test]
+        fn test_mock_info_challenge_handshake() {
+            // Some servers pad the challenge response
+            let mut challenge = CHALLENGE_RESPONSE.to_vec();
+            challenge.extend([0x00; 7]);
+            let transport = Arc::new(MockTransport::new(vec![
+                vec![challenge],
+                vec![INFO_RESPONSE.to_vec()],
+            ]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+
+            let (info, meta) = server.info_with_meta().unwrap();
+            assert_eq!(info.map(), "de_dust");
+            assert_eq!(meta.challenges, 1);
+
+            let mut answer = b"\xFF\xFF\xFF\xFFTSource Engine Query\0".to_vec();
+            answer.extend([0x4B, 0xA1, 0xD5, 0x22]);
+            assert_eq!(
+                transport.requests(),
+                vec![b"\xFF\xFF\xFF\xFFTSource Engine Query\0".to_vec(), answer]
+            );
+        }
+
+        #[test]
+        fn test_mock_players_challenge_handshake() {
+            let transport = Arc::new(MockTransport::new(vec![
+                vec![CHALLENGE_RESPONSE.to_vec()],
+                vec![PLAYERS_RESPONSE.to_vec()],
+            ]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+
+            let players = server.players().unwrap();
+            assert_eq!(players.len(), 2);
+            assert_eq!(players[0].name(), "[D]---->T.N.W<----");
+            assert_eq!(players[0].score(), 14);
+            assert_eq!(players[0].duration(), f32::from_bits(0x4400_97B4));
+            assert_eq!(players[1].name(), "Killer !!!");
+            assert_eq!(players[1].score(), 5);
+
+            assert_eq!(
+                transport.requests(),
+                vec![
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF],
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0x4B, 0xA1, 0xD5, 0x22],
+                ]
+            );
+        }
+
+        #[test]
+        fn test_mock_rules_split_response() {
+            let mut payload = vec![0xFF, 0xFF, 0xFF, 0xFF, 0x45];
+            put_short(&mut payload, 40);
+            for i in 0..40 {
+                put_cstring(&mut payload, &format!("sv_rule_{i}"));
+                put_cstring(&mut payload, &i.to_string());
+            }
+            let chunks: Vec<&[u8]> = payload.chunks(payload.len().div_ceil(3)).collect();
+            let mut packets: Vec<Vec<u8>> = chunks
+                .iter()
+                .enumerate()
+                .map(|(number, chunk)| split_packet(7, 3, number as u8, chunk))
+                .collect();
+            packets.swap(0, 2);
+
+            let largest_packet = packets.iter().map(Vec::len).max().unwrap();
+
+            let transport = Arc::new(MockTransport::new(vec![
+                vec![challenge_response()],
+                packets,
+            ]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+            let (rules, meta) = server.rules_with_meta().unwrap();
+            assert_eq!(rules.len(), 40);
+            assert_eq!(rules["sv_rule_39"], "39");
+            assert_eq!(
+                meta,
+                QueryMeta {
+                    fragments: 3,
+                    compressed: false,
+                    largest_packet,
+                    challenges: 1,
+                }
+            );
+        }
+
+        #[test]
+        fn test_mock_players_split_response() {
+            let players: Vec<Player> = (0..100)
+                .map(|i| Player::new(i, &format!("Player with a long name #{i}"), i.into(), 60.0))
+                .collect();
+            let mut payload = vec![0xFF, 0xFF, 0xFF, 0xFF];
+            payload.extend(players_to_payload(&players));
+
+            let total = payload.len().div_ceil(1200) as u8;
+            let packets: Vec<Vec<u8>> = payload
+                .chunks(1200)
+                .enumerate()
+                .map(|(number, chunk)| split_packet(9, total, number as u8, chunk))
+                .collect();
+            assert!(packets.len() > 1);
+
+            let transport = Arc::new(MockTransport::new(vec![packets]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+            assert_eq!(server.players().unwrap(), players);
+            assert_eq!(transport.requests().len(), 1);
+        }
+
+        #[test]
+        fn test_mock_short_datagrams() {
+            type Query = fn(&Server) -> Result<(), crate::QueryError>;
+            let queries: [Query; 4] = [
+                |server| server.info().map(drop),
+                |server| server.info_no_challenge().map(drop),
+                |server| server.players().map(drop),
+                |server| server.rules().map(drop),
+            ];
+            for datagram in [vec![], vec![0xFF; 4]] {
+                let too_short =
+                    format!("{} byte packet is too short for a response", datagram.len());
+                for query in queries {
+                    let transport = Arc::new(MockTransport::new(vec![vec![datagram.clone()]]));
+                    let server =
+                        Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+                    assert!(matches!(
+                        query(&server),
+                        Err(crate::QueryError::MalformedPacket(reason)) if reason == too_short
+                    ));
+                    assert_eq!(transport.requests().len(), 1);
+                }
+            }
+
+            let transport = Arc::new(MockTransport::new(vec![vec![vec![0x01; 8]]]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+            assert!(matches!(
+                server.players(),
+                Err(crate::QueryError::MalformedPacket(_))
+            ));
+        }
+
+        #[test]
+        fn test_mock_truncated_datagram() {
+            let transport = MockTransport::new(vec![vec![INFO_RESPONSE.to_vec()]]);
+            let addr = SERVER_ADDR.parse().unwrap();
+            transport.send_to(&[0x00], addr).unwrap();
+
+            // Like a socket, the rest of the datagram is dropped
+            let mut buffer = [0; 10];
+            assert_eq!(transport.recv_from(&mut buffer).unwrap(), (10, addr));
+            assert_eq!(buffer, INFO_RESPONSE[..10]);
+            assert_eq!(
+                transport.recv_from(&mut buffer).unwrap_err().kind(),
+                io::ErrorKind::WouldBlock
+            );
+
+            let mut response = INFO_RESPONSE.to_vec();
+            response.resize(crate::PACKET_SIZE + 100, 0x00);
+            let transport = Arc::new(MockTransport::new(vec![vec![response]]));
+            let server = Server::with_transport(addr, transport.clone());
+            assert!(matches!(
+                server.info(),
+                Err(crate::QueryError::ResponseTruncated {
+                    buffer_size: crate::PACKET_SIZE
+                })
+            ));
+        }
+
+        #[test]
+        fn test_mock_unanswered() {
+            let transport = Arc::new(MockTransport::new(vec![]));
+            let server = Server::with_transport(SERVER_ADDR.parse().unwrap(), transport.clone());
+            assert!(matches!(server.info(), Err(crate::QueryError::Timeout)));
+            assert_eq!(transport.requests().len(), 1);
+        }
+
+        #[cfg(feature = "socks5")]
+        #[test]
+        fn test_socks5_address() {
+            for addr in ["127.0.0.1:27015", "[2001:db8::1]:27016"] {
+                let addr: SocketAddr = addr.parse().unwrap();
+                let mut bytes = Vec::new();
+                put_socks_address(&mut bytes, addr);
+                bytes.push(0xAA);
+                assert_eq!(get_socks_address(&bytes), Some((addr, bytes.len() - 1)));
+            }
+
+            let mut bytes = vec![0x01, 127, 0, 0, 1];
+            assert_eq!(get_socks_address(&bytes), None);
+            bytes.extend(27015u16.to_be_bytes());
+            assert_eq!(bytes[5..], [0x69, 0x87]);
+            // Domain name
+            assert_eq!(
+                get_socks_address(&[0x03, 4, b'h', b'o', b's', b't', 0, 0]),
+                None
+            );
+        }
+
+        /// Spawn a SOCKS5 proxy answering the handshake with `method`, then relaying a single
+        /// datagram each way.
+        #[cfg(feature = "socks5")]
+        fn socks5_proxy(method: u8) -> SocketAddr {
+            use std::net::TcpListener;
+
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let proxy = listener.local_addr().unwrap();
+            std::thread::spawn(move || {
+                let (mut control, _) = listener.accept().unwrap();
+                let mut greeting = [0; 3];
+                control.read_exact(&mut greeting).unwrap();
+                assert_eq!(greeting, [0x05, 0x01, 0x00]);
+                control.write_all(&[0x05, method]).unwrap();
+                if method != 0x00 {
+                    return;
+                }
+
+                let mut request = [0; 10];
+                control.read_exact(&mut request).unwrap();
+                assert_eq!(request[..4], [0x05, 0x03, 0x00, 0x01]);
+                let relay = UdpSocket::bind("127.0.0.1:0").unwrap();
+                // Unspecified, standing for the address of the proxy
+                let mut reply = vec![0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0];
+                reply.extend(relay.local_addr().unwrap().port().to_be_bytes());
+                control.write_all(&reply).unwrap();
+
+                let upstream = UdpSocket::bind("127.0.0.1:0").unwrap();
+                let mut buffer = [0; 2048];
+                let (size, client) = relay.recv_from(&mut buffer).unwrap();
+                let (target, length) = get_socks_address(&buffer[3..size]).unwrap();
+                upstream.send_to(&buffer[3 + length..size], target).unwrap();
+
+                let (size, source) = upstream.recv_from(&mut buffer).unwrap();
+                let mut packet = vec![0x00, 0x00, 0x00];
+                put_socks_address(&mut packet, source);
+                packet.extend(&buffer[..size]);
+                relay.send_to(&packet, client).unwrap();
+                // Keep the association open until the client is done
+                let _ = control.read(&mut buffer);
+            });
+            proxy
+        }
+
+        #[cfg(feature = "socks5")]
+        #[test]
+        fn test_socks5_transport() {
+            let game = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let target = game.local_addr().unwrap();
+            std::thread::spawn(move || {
+                let mut buffer = [0; 2048];
+                let (size, client) = game.recv_from(&mut buffer).unwrap();
+                assert_eq!(buffer[..size], crate::request::info(None));
+                game.send_to(&INFO_RESPONSE, client).unwrap();
+            });
+
+            let server = Server::via_proxy(socks5_proxy(0x00), target).unwrap();
+            assert_eq!(server.addr(), target);
+            assert_eq!(server.info().unwrap().map(), "de_dust");
+
+            assert!(Server::via_proxy(socks5_proxy(0xFF), target).is_err());
+        }
+    }
+}
+
+pub mod protocol {
+    //! Parsers of the server query responses, free of any socket.
+    //!
+    //! Each function takes a whole single-packet datagram as received, starting with its
+    //! `FF FF FF FF` header. Split responses must be reassembled first. [`Server`](crate::Server)
+    //! parses its responses with the same functions.
+    //!
+    //! ```
+    //! use valve_server_query::protocol;
+    //!
+    //! let datagram = [0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x4B, 0xA1, 0xD5, 0x22];
+    //! assert_eq!(protocol::parse_challenge(&datagram).unwrap(), [0x4B, 0xA1, 0xD5, 0x22]);
+    //! ```
+
+    use crate::error::QueryError;
+    use crate::models::info::Info;
+    use crate::models::Player;
+    use crate::server::Rules;
+    use crate::types::{Byte, Encoding, PacketReader, DEFAULT_MAX_STRING_LEN};
+    use crate::SIMPLE_RESPONSE_HEADER;
+
+    /// Default maximum number of rules parsed from an A2S_RULES response.
+    pub(crate) const DEFAULT_MAX_RULES: usize = 4096;
+    /// Default maximum number of players parsed from an A2S_PLAYER response.
+    pub(crate) const DEFAULT_MAX_PLAYERS: usize = Byte::MAX as usize;
+
+    /// Kind of a response, named by the header byte following the simple response header.
+    ///
+    /// ```
+    /// use valve_server_query::protocol::ResponseKind;
+    ///
+    /// assert_eq!(ResponseKind::try_from_byte(0x44), Some(ResponseKind::Players));
+    /// assert_eq!(ResponseKind::Players.as_byte(), b'D');
+    /// ```
+    #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+    pub enum ResponseKind {
+        /// A2S_INFO response, 'I' (0x49).
+        Info,
+        /// Obsolete GoldSource A2S_INFO response, 'm' (0x6D).
+        GoldSourceInfo,
+        /// A2S_PLAYER response, 'D' (0x44).
+        Players,
+        /// A2S_RULES response, 'E' (0x45).
+        Rules,
+        /// Challenge number to answer, 'A' (0x41).
+        Challenge,
+        /// A2A_PING response, 'j' (0x6A).
+        Ping,
+    }
+
+    impl ResponseKind {
+        /// Every kind of response.
+        pub const ALL: [Self; 6] = [
+            Self::Info,
+            Self::GoldSourceInfo,
+            Self::Players,
+            Self::Rules,
+            Self::Challenge,
+            Self::Ping,
+        ];
+
+        /// Header byte of the response.
+        pub const fn as_byte(self) -> Byte {
+            match self {
+                Self::Info => 0x49,
+                Self::GoldSourceInfo => 0x6D,
+                Self::Players => 0x44,
+                Self::Rules => 0x45,
+                Self::Challenge => 0x41,
+                Self::Ping => 0x6A,
+            }
+        }
+
+        /// Kind of response with the given header byte, or `None` for an unknown header.
+        pub fn try_from_byte(byte: Byte) -> Option<Self> {
+            Self::ALL.into_iter().find(|kind| kind.as_byte() == byte)
+        }
+    }
+
+    /// Kind of a single-packet response datagram, from the header byte following the simple
+    /// response header.
+    pub(crate) fn response_kind(datagram: &[u8]) -> Option<ResponseKind> {
+        datagram
+            .get(SIMPLE_RESPONSE_HEADER.len())
+            .copied()
+            .and_then(ResponseKind::try_from_byte)
+    }
+
+    /// Parse an A2S_INFO response datagram.
+    pub fn parse_info(datagram: &[u8]) -> Result<Info, QueryError> {
+        ParseOptions::default().parse_info(datagram)
+    }
+
+    /// Parse an A2S_PLAYER response datagram.
+    pub fn parse_players(datagram: &[u8]) -> Result<Vec<Player>, QueryError> {
+        ParseOptions::default().parse_players(datagram)
+    }
+
+    /// Parse an A2S_RULES response datagram.
+    pub fn parse_rules(datagram: &[u8]) -> Result<Rules, QueryError> {
+        ParseOptions::default().parse_rules(datagram)
+    }
+
+    /// Parse an A2S_RULES response datagram, keeping every rule in the order it was sent,
+    /// including rules of the same name.
+    pub fn parse_rule_pairs(datagram: &[u8]) -> Result<Vec<(String, String)>, QueryError> {
+        ParseOptions::default().parse_rule_pairs(datagram)
+    }
+
+    /// Parse a rule count and that many rules, as following the header of an A2S_RULES
+    /// payload, also returning the number of bytes they took.
+    ///
+    /// Parsing stops after the declared rules, so anything following them is left for the
+    /// caller.
+    pub fn parse_rules_consumed(bytes: &[u8]) -> Result<(Rules, usize), QueryError> {
+        ParseOptions::default().parse_rules_consumed(bytes)
+    }
+
+    /// Read the challenge number of a challenge response, the four bytes following the simple
+    /// header and the `0x41` header. Anything after them is ignored.
+    pub fn parse_challenge(datagram: &[u8]) -> Result<[u8; 4], QueryError> {
+        let mut reader = PacketReader::new(datagram);
+        let header = reader.read_bytes(SIMPLE_RESPONSE_HEADER.len() + 1)?[4];
+        let expected = ResponseKind::Challenge.as_byte();
+        if !datagram.starts_with(&SIMPLE_RESPONSE_HEADER) || header != expected {
+            return Err(QueryError::UnexpectedHeader {
+                expected,
+                found: header,
+            });
+        }
+        let challenge = reader.read_bytes(4)?;
+        Ok(challenge
+            .try_into()
+            .expect("slice has the requested length"))
+    }
+
+    /// Encoding and limits responses are parsed with, configured on a
+    /// [`ServerBuilder`](crate::ServerBuilder) for the responses of a server.
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    pub struct ParseOptions {
+        /// How strings are decoded.
+        pub encoding: Encoding,
+        /// Maximum length in bytes of a string.
+        pub max_string_len: usize,
+        /// Maximum number of rules in an A2S_RULES response.
+        pub max_rules: usize,
+        /// Maximum number of players in an A2S_PLAYER response.
+        pub max_players: usize,
+        /// Whether player records carry the extra fields of The Ship.
+        pub the_ship: bool,
+    }
+
+    impl Default for ParseOptions {
+        fn default() -> Self {
+            Self {
+                encoding: Encoding::default(),
+                max_string_len: DEFAULT_MAX_STRING_LEN,
+                max_rules: DEFAULT_MAX_RULES,
+                max_players: DEFAULT_MAX_PLAYERS,
+                the_ship: false,
+            }
+        }
+    }
+
+    impl ParseOptions {
+        /// Like [`parse_info`], with these options.
+        pub fn parse_info(&self, datagram: &[u8]) -> Result<Info, QueryError> {
+            self.info_from_payload(payload(datagram)?)
+        }
+        /// Like [`parse_players`], with these options.
+        pub fn parse_players(&self, datagram: &[u8]) -> Result<Vec<Player>, QueryError> {
+            self.players_from_payload(payload(datagram)?)
+        }
+        /// Like [`parse_rules`], with these options.
+        pub fn parse_rules(&self, datagram: &[u8]) -> Result<Rules, QueryError> {
+            self.rules_from_payload(payload(datagram)?)
+        }
+        /// Like [`parse_rule_pairs`], with these options.
+        pub fn parse_rule_pairs(
+            &self,
+            datagram: &[u8],
+        ) -> Result<Vec<(String, String)>, QueryError> {
+            self.rule_pairs_from_payload(payload(datagram)?)
+        }
+        /// Like [`parse_rules_consumed`], with these options.
+        pub fn parse_rules_consumed(&self, bytes: &[u8]) -> Result<(Rules, usize), QueryError> {
+            let mut reader = self.reader(bytes);
+            let rule_count = self.read_rule_count(&mut reader)?;
+            let rules = self.read_rule_pairs(&mut reader, Some(rule_count))?;
+            Ok((rules.into(), reader.position()))
+        }
+
+        /// Reader over a response payload, decoding strings with the encoding and limiting
+        /// their length.
+        pub(crate) fn reader<'a>(&self, payload: &'a [u8]) -> PacketReader<'a> {
+            PacketReader::new(payload)
+                .with_encoding(self.encoding)
+                .with_max_string_len(self.max_string_len)
+        }
+
+        /// Parse an A2S_INFO response, starting at the header byte.
+        pub(crate) fn info_from_payload(&self, payload: &[u8]) -> Result<Info, QueryError> {
+            trace!("parsing A2S_INFO payload of {} bytes", payload.len());
+            Info::from_reader(self.reader(payload))
+        }
+
+        /// Parse an A2S_PLAYER response: header, player count, then the players themselves.
+        ///
+        /// As many players as declared are parsed, and anything following them is ignored.
+        /// Fewer records than declared fail with [`QueryError::CountMismatch`]. For The Ship,
+        /// each player record carries its extra fields.
+        pub(crate) fn players_from_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<Vec<Player>, QueryError> {
+            trace!("parsing A2S_PLAYER payload of {} bytes", payload.len());
+            check_header(payload, ResponseKind::Players)?;
+            let mut reader = self.reader(&payload[1..]);
+            let player_count: Byte = reader.read_u8()?;
+            if player_count as usize > self.max_players {
+                return Err(QueryError::TooManyEntries {
+                    limit: self.max_players,
+                });
+            }
+
+            let mut players: Vec<Player> = Vec::with_capacity(player_count as usize);
+            for _ in 0..player_count {
+                if reader.remaining() == 0 {
+                    return Err(QueryError::CountMismatch {
+                        expected: player_count as usize,
+                        found: players.len(),
+                    });
+                }
+                let player = if self.the_ship {
+                    Player::from_reader_the_ship(&mut reader)?
+                } else {
+                    Player::from_reader(&mut reader)?
+                };
+                players.push(player);
+            }
+
+            Ok(players)
+        }
+
+        /// Parse an A2S_RULES response: header, rule count, then the rules themselves.
+        ///
+        /// The number of rules parsed must match the rule count declared by the server.
+        pub(crate) fn rules_from_payload(&self, payload: &[u8]) -> Result<Rules, QueryError> {
+            Ok(self.rule_pairs_from_payload(payload)?.into())
+        }
+
+        /// Parse an A2S_RULES response like [`ParseOptions::rules_from_payload`], keeping every rule
+        /// in the order it was sent.
+        ///
+        /// A declared rule count beyond the maximum number of rules fails before any rule is
+        /// parsed.
+        pub(crate) fn rule_pairs_from_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<Vec<(String, String)>, QueryError> {
+            let (rules, rule_count) = self.rule_pairs_with_count_from_payload(payload)?;
+            if rules.len() != rule_count {
+                return Err(QueryError::CountMismatch {
+                    expected: rule_count,
+                    found: rules.len(),
+                });
+            }
+
+            Ok(rules)
+        }
+
+        /// Parse an A2S_RULES response, along with the rule count declared by the server,
+        /// which is not checked against the rules parsed.
+        pub(crate) fn rules_with_count_from_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<(Rules, usize), QueryError> {
+            let (rules, rule_count) = self.rule_pairs_with_count_from_payload(payload)?;
+            Ok((rules.into(), rule_count))
+        }
+
+        fn rule_pairs_with_count_from_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<(Vec<(String, String)>, usize), QueryError> {
+            trace!("parsing A2S_RULES payload of {} bytes", payload.len());
+            check_header(payload, ResponseKind::Rules)?;
+            let mut reader = self.reader(&payload[1..]);
+
+            let rule_count = self.read_rule_count(&mut reader)?;
+            let rules = self.read_rule_pairs(&mut reader, None)?;
+            Ok((rules, rule_count))
+        }
+
+        /// Read the rule count, failing if it is beyond the maximum number of rules.
+        fn read_rule_count(&self, reader: &mut PacketReader) -> Result<usize, QueryError> {
+            let rule_count = reader.read_i16()? as usize;
+            if rule_count > self.max_rules {
+                return Err(QueryError::TooManyEntries {
+                    limit: self.max_rules,
+                });
+            }
+            Ok(rule_count)
+        }
+
+        /// Read `count` rule pairs, or every rule up to the end of the reader if `None`,
+        /// failing beyond the maximum number of rules.
+        pub(crate) fn read_rule_pairs(
+            &self,
+            reader: &mut PacketReader,
+            count: Option<usize>,
+        ) -> Result<Vec<(String, String)>, QueryError> {
+            let mut rules = Vec::with_capacity(count.unwrap_or_default());
+            while count.map_or(reader.remaining() > 0, |count| rules.len() < count) {
+                if rules.len() == self.max_rules {
+                    return Err(QueryError::TooManyEntries {
+                        limit: self.max_rules,
+                    });
+                }
+                let name = reader.read_cstring()?;
+                let value = reader.read_cstring()?;
+
+                rules.push((name, value));
+            }
+
+            Ok(rules)
+        }
+
+        /// Parse the rules from the start of a truncated A2S_RULES payload, stopping at the
+        /// first rule cut off.
+        pub(crate) fn rules_from_partial_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<Rules, QueryError> {
+            check_header(payload, ResponseKind::Rules)?;
+            // Header, Rule Count
+            let mut reader = self.reader(payload.get(3..).unwrap_or_default());
+
+            let mut rules = Rules::default();
+            let mut parsed = 0;
+            while reader.remaining() > 0 {
+                if parsed == self.max_rules {
+                    return Err(QueryError::TooManyEntries {
+                        limit: self.max_rules,
+                    });
+                }
+                parsed += 1;
+                match (reader.read_cstring(), reader.read_cstring()) {
+                    (Ok(name), Ok(value)) => rules.push(name, value),
+                    _ => break,
+                }
+            }
+
+            Ok(rules)
+        }
+    }
+
+    /// Payload of a single-packet datagram, following its simple header.
+    fn payload(datagram: &[u8]) -> Result<&[u8], QueryError> {
+        check_packet_len(datagram)?;
+        if !datagram.starts_with(&SIMPLE_RESPONSE_HEADER) {
+            return Err(QueryError::MalformedPacket(
+                "not a single-packet response".to_string(),
+            ));
+        }
+        Ok(&datagram[SIMPLE_RESPONSE_HEADER.len()..])
+    }
+
+    /// Check that a packet is long enough to hold the 4 byte header and the response type byte,
+    /// before its headers are looked at.
+    pub(crate) fn check_packet_len(packet: &[u8]) -> Result<(), QueryError> {
+        if packet.len() < SIMPLE_RESPONSE_HEADER.len() + 1 {
+            return Err(QueryError::MalformedPacket(format!(
+                "{} byte packet is too short for a response",
+                packet.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that a response payload starts with the header byte of the expected response type.
+    fn check_header(payload: &[u8], kind: ResponseKind) -> Result<(), QueryError> {
+        let expected = kind.as_byte();
+        match payload.first() {
+            Some(&header) if header == expected => Ok(()),
+            found => Err(QueryError::UnexpectedHeader {
+                expected,
+                found: found.copied().unwrap_or_default(),
+            }),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::models::players_to_payload;
+        use crate::server::rules_to_bytes;
+
+        #[test]
+        fn test_response_kind_round_trip() {
+            for kind in ResponseKind::ALL {
+                assert_eq!(ResponseKind::try_from_byte(kind.as_byte()), Some(kind));
+            }
+            for byte in 0..=Byte::MAX {
+                if let Some(kind) = ResponseKind::try_from_byte(byte) {
+                    assert_eq!(kind.as_byte(), byte);
+                }
+            }
+            let bytes: Vec<Byte> = ResponseKind::ALL
+                .iter()
+                .map(|kind| kind.as_byte())
+                .collect();
+            assert_eq!(bytes, [0x49, 0x6D, 0x44, 0x45, 0x41, 0x6A]);
+
+            assert_eq!(
+                response_kind(&[0xFF, 0xFF, 0xFF, 0xFF, 0x41, 1, 2, 3, 4]),
+                Some(ResponseKind::Challenge)
+            );
+            assert_eq!(response_kind(&[0xFF, 0xFF, 0xFF, 0xFF, 0x00]), None);
+            assert_eq!(response_kind(&[0xFF, 0xFF, 0xFF, 0xFF]), None);
+        }
+
+        #[test]
+        fn test_parse_challenge() {
+            let packet = [0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x00, 0x12, 0x00, 0x00];
+            assert_eq!(parse_challenge(&packet).unwrap(), [0x00, 0x12, 0x00, 0x00]);
+
+            // Trailing data after the challenge is not part of it
+            let packet = [
+                0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x01, 0x02, 0x03, 0x04, 0x00, 0x05,
+            ];
+            assert_eq!(parse_challenge(&packet).unwrap(), [0x01, 0x02, 0x03, 0x04]);
+
+            assert!(matches!(
+                parse_challenge(&[0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x01, 0x02]),
+                Err(QueryError::UnexpectedEnd {
+                    position: 5,
+                    needed: 4
+                })
+            ));
+            assert!(matches!(
+                parse_challenge(&[0xFF, 0xFF, 0xFF, 0xFF, 0x44, 0x01, 0x02, 0x03, 0x04]),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x41,
+                    found: 0x44
+                })
+            ));
+        }
+
+        #[test]
+        fn test_parse_datagrams() {
+            let players = vec![
+                Player::new(0, "Alice", 3, 12.5),
+                Player::new(1, "Bob", 0, 1.0),
+            ];
+            let mut datagram = SIMPLE_RESPONSE_HEADER.to_vec();
+            datagram.extend(players_to_payload(&players));
+            assert_eq!(parse_players(&datagram).unwrap(), players);
+            assert!(matches!(
+                parse_rules(&datagram),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x45,
+                    found: 0x44
+                })
+            ));
+
+            let rules = Rules::from(vec![("sv_cheats".to_string(), "0".to_string())]);
+            let mut datagram = SIMPLE_RESPONSE_HEADER.to_vec();
+            datagram.extend(rules_to_bytes(&rules));
+            assert_eq!(parse_rules(&datagram).unwrap(), rules);
+
+            let mut datagram = SIMPLE_RESPONSE_HEADER.to_vec();
+            datagram.extend([0x49, 0x11]);
+            datagram.extend(b"Test Server\0de_dust2\0cstrike\0Counter-Strike\0");
+            datagram.extend(10i16.to_le_bytes());
+            datagram.extend([5, 24, 0, b'd', b'l', 0x00, 0x01]);
+            datagram.extend(b"1.0.0.0\0");
+            assert_eq!(parse_info(&datagram).unwrap().name(), "Test Server");
+
+            // Payloads without their header, and split packets, are not datagrams
+            assert!(matches!(
+                parse_info(&datagram[4..]),
+                Err(QueryError::MalformedPacket(_))
+            ));
+            assert!(matches!(
+                parse_info(&[0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x00, 0x02, 0x00]),
+                Err(QueryError::MalformedPacket(_))
+            ));
+            assert!(matches!(
+                parse_info(&[0xFF, 0xFF, 0xFF, 0xFF]),
+                Err(QueryError::MalformedPacket(_))
+            ));
+        }
+
+        #[test]
+        fn test_parse_rules_consumed() {
+            let mut bytes = 2i16.to_le_bytes().to_vec();
+            bytes.extend(b"sv_cheats\x000\x00mp_timelimit\x0030\x00");
+            let length = bytes.len();
+            bytes.extend(b"next\x00");
+
+            let (rules, consumed) = parse_rules_consumed(&bytes).unwrap();
+            assert_eq!(consumed, length);
+            assert_eq!(rules.len(), 2);
+            assert_eq!(rules["mp_timelimit"], "30");
+
+            assert_eq!(parse_rules_consumed(&[0x00, 0x00]).unwrap().1, 2);
+            assert!(parse_rules_consumed(&bytes[..length - 1]).is_err());
+
+            let options = ParseOptions {
+                max_rules: 1,
+                ..ParseOptions::default()
+            };
+            assert!(matches!(
+                options.parse_rules_consumed(&bytes),
+                Err(QueryError::TooManyEntries { limit: 1 })
+            ));
+        }
+
+        #[test]
+        fn test_parse_players_empty() {
+            let datagram = [0xFF, 0xFF, 0xFF, 0xFF, 0x44, 0x00];
+            assert_eq!(parse_players(&datagram).unwrap(), vec![]);
+            let the_ship = ParseOptions {
+                the_ship: true,
+                ..Default::default()
+            };
+            assert_eq!(the_ship.parse_players(&datagram).unwrap(), vec![]);
+            assert_eq!(Player::get_players(&[]).unwrap(), vec![]);
+            assert_eq!(players_to_payload(&[]), datagram[4..]);
+
+            // Only the declared count of records is read, after the count byte
+            let mut padded = datagram.to_vec();
+            padded.extend([0x00; 12]);
+            assert_eq!(parse_players(&padded).unwrap(), vec![]);
+
+            // Without the count byte
+            assert!(matches!(
+                parse_players(&datagram[..5]),
+                Err(QueryError::UnexpectedEnd { needed: 1, .. })
+            ));
+        }
+
+        #[test]
+        fn test_parse_players_count() {
+            // The last player has an empty name, the smallest record there is
+            let players = vec![Player::new(0, "Alice", 3, 12.5), Player::new(1, "", 0, 1.0)];
+            let mut datagram = SIMPLE_RESPONSE_HEADER.to_vec();
+            datagram.extend(players_to_payload(&players));
+            assert_eq!(parse_players(&datagram).unwrap(), players);
+            assert_eq!(Player::get_players(&datagram[6..]).unwrap(), players);
+
+            // Fewer players than declared
+            let mut short = datagram.clone();
+            short[5] = 3;
+            assert!(matches!(
+                parse_players(&short),
+                Err(QueryError::CountMismatch {
+                    expected: 3,
+                    found: 2
+                })
+            ));
+
+            // More players than declared
+            let mut long = datagram.clone();
+            long[5] = 1;
+            assert_eq!(parse_players(&long).unwrap(), players[..1]);
+
+            // A record cut short is not a missing record
+            assert!(matches!(
+                parse_players(&datagram[..datagram.len() - 1]),
+                Err(QueryError::UnexpectedEnd { .. })
+            ));
+        }
+
+        #[test]
+        fn test_parse_options() {
+            let players = vec![Player::new(0, "Alice", 3, 12.5).with_the_ship(1, 500)];
+            let mut datagram = SIMPLE_RESPONSE_HEADER.to_vec();
+            datagram.extend(players_to_payload(&players));
+
+            let options = ParseOptions {
+                the_ship: true,
+                ..Default::default()
+            };
+            assert_eq!(options.parse_players(&datagram).unwrap(), players);
+
+            let options = ParseOptions {
+                max_players: 0,
+                ..Default::default()
+            };
+            assert!(matches!(
+                options.parse_players(&datagram),
+                Err(QueryError::TooManyEntries { limit: 0 })
+            ));
+        }
+    }
+}
+
+pub mod request {
+    //! Builders of the server query requests, free of any socket.
+    //!
+    //! Each function returns a whole datagram, starting with its `FF FF FF FF` header, as
+    //! [`Server`](crate::Server) sends it.
+    //!
+    //! ```
+    //! use valve_server_query::request;
+    //!
+    //! assert_eq!(
+    //!     request::players(request::REQUEST_CHALLENGE),
+    //!     [0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF]
+    //! );
+    //! ```
+
+    use crate::types::{Byte, PacketWriter};
+
+    /// Challenge sent to ask the server for a challenge number.
+    pub const REQUEST_CHALLENGE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+    /// Payload of an A2S_INFO request.
+    const INFO_PAYLOAD: &str = "Source Engine Query";
+
+    /// Kind of a request, named by the header byte following the simple header.
+    ///
+    /// ```
+    /// use valve_server_query::request::RequestKind;
+    ///
+    /// assert_eq!(RequestKind::try_from_byte(0x55), Some(RequestKind::Players));
+    /// assert_eq!(RequestKind::Players.as_byte(), b'U');
+    /// ```
+    #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+    pub enum RequestKind {
+        /// A2S_INFO, 'T' (0x54).
+        Info,
+        /// A2S_PLAYER, 'U' (0x55).
+        Players,
+        /// A2S_RULES, 'V' (0x56).
+        Rules,
+        /// A2S_SERVERQUERY_GETCHALLENGE, 'W' (0x57).
+        GetChallenge,
+        /// A2A_PING, 'i' (0x69).
+        Ping,
+    }
+
+    impl RequestKind {
+        /// Every kind of request.
+        pub const ALL: [Self; 5] = [
+            Self::Info,
+            Self::Players,
+            Self::Rules,
+            Self::GetChallenge,
+            Self::Ping,
+        ];
+
+        /// Header byte of the request.
+        pub const fn as_byte(self) -> Byte {
+            match self {
+                Self::Info => 0x54,
+                Self::Players => 0x55,
+                Self::Rules => 0x56,
+                Self::GetChallenge => 0x57,
+                Self::Ping => 0x69,
+            }
+        }
+
+        /// Kind of request with the given header byte, or `None` for an unknown header.
+        pub fn try_from_byte(byte: Byte) -> Option<Self> {
+            Self::ALL.into_iter().find(|kind| kind.as_byte() == byte)
+        }
+    }
+
+    /// Build an A2S_INFO request, answering `challenge` if the server sent one.
+    pub fn info(challenge: Option<[u8; 4]>) -> Vec<u8> {
+        PacketWriter::new()
+            .write_simple_header()
+            .write_u8(RequestKind::Info.as_byte())
+            .write_cstring(INFO_PAYLOAD)
+            .write_challenge(challenge.as_ref().map_or(&[], |c| &c[..]))
+            .into_bytes()
+    }
+
+    /// Build an A2S_PLAYER request, sending [`REQUEST_CHALLENGE`] to ask for a challenge.
+    pub fn players(challenge: [u8; 4]) -> Vec<u8> {
+        PacketWriter::new()
+            .write_simple_header()
+            .write_u8(RequestKind::Players.as_byte())
+            .write_challenge(&challenge)
+            .into_bytes()
+    }
+
+    /// Build an A2S_RULES request, sending [`REQUEST_CHALLENGE`] to ask for a challenge.
+    pub fn rules(challenge: [u8; 4]) -> Vec<u8> {
+        PacketWriter::new()
+            .write_simple_header()
+            .write_u8(RequestKind::Rules.as_byte())
+            .write_challenge(&challenge)
+            .into_bytes()
+    }
+
+    /// Build an A2S_SERVERQUERY_GETCHALLENGE request.
+    pub fn get_challenge() -> Vec<u8> {
+        header(RequestKind::GetChallenge)
+    }
+
+    /// Build an A2A_PING request.
+    pub fn ping() -> Vec<u8> {
+        header(RequestKind::Ping)
+    }
+
+    /// Build a request made of the simple header and the header byte of `kind` alone.
+    fn header(kind: RequestKind) -> Vec<u8> {
+        PacketWriter::new()
+            .write_simple_header()
+            .write_u8(kind.as_byte())
+            .into_bytes()
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        #[test]
+        fn test_info() {
+            // "Source Engine Query"
+            let mut expected = vec![
+                0xFF, 0xFF, 0xFF, 0xFF, 0x54, 0x53, 0x6F, 0x75, 0x72, 0x63, 0x65, 0x20, 0x45, 0x6E,
+                0x67, 0x69, 0x6E, 0x65, 0x20, 0x51, 0x75, 0x65, 0x72, 0x79, 0x00,
+            ];
+            assert_eq!(info(None), expected);
+
+            expected.extend([0x0A, 0x08, 0x5E, 0xEA]);
+            assert_eq!(info(Some([0x0A, 0x08, 0x5E, 0xEA])), expected);
+        }
+
+        #[test]
+        fn test_players_and_rules() {
+            assert_eq!(
+                players(REQUEST_CHALLENGE),
+                [0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF]
+            );
+            assert_eq!(
+                players([0x4B, 0xA1, 0xD5, 0x22]),
+                [0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0x4B, 0xA1, 0xD5, 0x22]
+            );
+            assert_eq!(
+                rules(REQUEST_CHALLENGE),
+                [0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0xFF, 0xFF, 0xFF, 0xFF]
+            );
+            assert_eq!(
+                rules([0x4B, 0xA1, 0xD5, 0x22]),
+                [0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0x4B, 0xA1, 0xD5, 0x22]
+            );
+        }
+
+        #[test]
+        fn test_request_kind_round_trip() {
+            for kind in RequestKind::ALL {
+                assert_eq!(RequestKind::try_from_byte(kind.as_byte()), Some(kind));
+            }
+            for byte in 0..=Byte::MAX {
+                if let Some(kind) = RequestKind::try_from_byte(byte) {
+                    assert_eq!(kind.as_byte(), byte);
+                }
+            }
+            let bytes: Vec<Byte> = RequestKind::ALL.iter().map(|kind| kind.as_byte()).collect();
+            assert_eq!(bytes, [0x54, 0x55, 0x56, 0x57, 0x69]);
+        }
+
+        #[test]
+        fn test_header_only() {
+            assert_eq!(get_challenge(), [0xFF, 0xFF, 0xFF, 0xFF, 0x57]);
+            assert_eq!(ping(), [0xFF, 0xFF, 0xFF, 0xFF, 0x69]);
+        }
+    }
+}
+
+pub mod server {
+
+    use crate::{MULTI_PACKET_RESPONSE_HEADER, PACKET_SIZE, SIMPLE_RESPONSE_HEADER};
+    use std::collections::{BTreeMap, HashMap};
+    use std::error::Error;
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+    use std::ops::{Deref, DerefMut};
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::Poll;
+    use std::time::{Duration, Instant};
+
+    use crate::error::QueryError;
+    use crate::models::dayz::DayZRules;
+    use crate::models::info::Info;
+    use crate::models::Player;
+    use crate::protocol::{
+        check_packet_len, parse_challenge, response_kind, ParseOptions, ResponseKind,
+    };
+    use crate::request::{self, REQUEST_CHALLENGE};
+    use crate::transport::Transport;
+    use crate::types::{put_cstring, put_short, Byte, Encoding, Long, Short};
+    use crate::utils::SplitPacketHeader;
+
+    /// Server rules (console variables) as returned by A2S_RULES, keyed by name.
+    ///
+    /// Dereferences to the underlying `HashMap`, and offers typed getters for common values.
+    ///
+    /// A rule sent more than once keeps its last value in the map; the values it had before
+    /// are kept in [`Rules::duplicates`].
+    ///
+    /// With the `serde` feature, rules serialize as a map of names to values in
+    /// human-readable formats such as JSON, leaving out the duplicates, which binary formats
+    /// keep.
+    #[derive(Debug, Default, PartialEq, Eq, Clone)]
+    pub struct Rules {
+        map: HashMap<String, String>,
+        duplicates: Vec<(String, String)>,
+    }
+
+    impl Rules {
+        /// Parse a rule as a boolean.
+        ///
+        /// Accepts Source's "0"/"1" convention as well as "true"/"false" (case-insensitive).
+        pub fn get_bool(&self, key: &str) -> Option<bool> {
+            let value = self.map.get(key)?.trim();
+            match value {
+                "1" => Some(true),
+                "0" => Some(false),
+                _ if value.eq_ignore_ascii_case("true") => Some(true),
+                _ if value.eq_ignore_ascii_case("false") => Some(false),
+                _ => None,
+            }
+        }
+        /// Parse a rule as an integer.
+        pub fn get_i64(&self, key: &str) -> Option<i64> {
+            self.map.get(key)?.trim().parse().ok()
+        }
+        /// Parse a rule as a floating point number.
+        pub fn get_f64(&self, key: &str) -> Option<f64> {
+            self.map.get(key)?.trim().parse().ok()
+        }
+        /// Rules that were overwritten by a later rule of the same name, in the order they
+        /// were sent.
+        pub fn duplicates(&self) -> &[(String, String)] {
+            &self.duplicates
+        }
+        /// Add a rule, keeping any value it already had as a duplicate.
+        pub(crate) fn push(&mut self, name: String, value: String) {
+            if let Some(previous) = self.map.insert(name.clone(), value) {
+                self.duplicates.push((name, previous));
+            }
+        }
+        /// Consume the rules, returning the underlying map.
+        pub fn into_inner(self) -> HashMap<String, String> {
+            self.map
+        }
+        /// Compare with rules received later, such as from the next poll of the server.
+        ///
+        /// Only the rules in the map are compared, not the [duplicates](Rules::duplicates).
+        pub fn diff(&self, other: &Rules) -> RulesDiff {
+            let mut diff = RulesDiff::default();
+            for (name, value) in &self.map {
+                match other.map.get(name) {
+                    None => {
+                        diff.removed.insert(name.clone(), value.clone());
+                    }
+                    Some(new) if new != value => {
+                        diff.changed
+                            .insert(name.clone(), (value.clone(), new.clone()));
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (name, value) in &other.map {
+                if !self.map.contains_key(name) {
+                    diff.added.insert(name.clone(), value.clone());
+                }
+            }
+            diff
+        }
+    }
+
+    /// Rules that differ between two sets of rules, see [`Rules::diff`], sorted by name.
+    #[derive(Debug, Default, PartialEq, Eq, Clone)]
+    pub struct RulesDiff {
+        /// Rules only in the later rules, with their value.
+        pub added: BTreeMap<String, String>,
+        /// Rules only in the earlier rules, with the value they had.
+        pub removed: BTreeMap<String, String>,
+        /// Rules in both with a different value, with the old value then the new one.
+        pub changed: BTreeMap<String, (String, String)>,
+    }
+
+    impl RulesDiff {
+        /// Whether the rules are the same.
+        pub fn is_empty(&self) -> bool {
+            self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Rules {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_map(&self.map)
+            } else {
+                // Duplicates first, so that the rules collect back to the same map
+                serializer.collect_seq(
+                    self.duplicates
+                        .iter()
+                        .map(|(name, value)| (name, value))
+                        .chain(&self.map),
+                )
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::Deserialize<'de> for Rules {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                Ok(HashMap::deserialize(deserializer)?.into())
+            } else {
+                Ok(Vec::<(String, String)>::deserialize(deserializer)?.into())
+            }
+        }
+    }
+
+    impl Deref for Rules {
+        type Target = HashMap<String, String>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.map
+        }
+    }
+
+    impl DerefMut for Rules {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.map
+        }
+    }
+
+    impl From<HashMap<String, String>> for Rules {
+        fn from(map: HashMap<String, String>) -> Self {
+            Self {
+                map,
+                duplicates: Vec::new(),
+            }
+        }
+    }
+
+    /// Build rules from pairs such as those of
+    /// [`parse_rule_pairs`](crate::protocol::parse_rule_pairs), keeping the values overwritten
+    /// by a later rule of the same name as duplicates.
+    impl From<Vec<(String, String)>> for Rules {
+        fn from(pairs: Vec<(String, String)>) -> Self {
+            pairs.into_iter().collect()
+        }
+    }
+
+    impl FromIterator<(String, String)> for Rules {
+        fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+            let mut rules = Self::default();
+            for (name, value) in iter {
+                rules.push(name, value);
+            }
+            rules
+        }
+    }
+
+    impl IntoIterator for Rules {
+        type Item = (String, String);
+        type IntoIter = std::collections::hash_map::IntoIter<String, String>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.map.into_iter()
+        }
+    }
+
+    impl<'a> IntoIterator for &'a Rules {
+        type Item = (&'a String, &'a String);
+        type IntoIter = std::collections::hash_map::Iter<'a, String, String>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.map.iter()
+        }
+    }
+
+    /// Serialize an A2S_RULES payload: the header, the rule count, then the rules themselves.
+    ///
+    /// The payload starts at the header byte, without the simple response header. The
+    /// [duplicates](Rules::duplicates) come first, so that parsing the payload gives back the
+    /// same rules; the order of the other rules is unspecified. Only the first 32767 rules are
+    /// written, the most the count can declare.
+    ///
+    /// ```
+    /// use valve_server_query::protocol::parse_rules_consumed;
+    /// use valve_server_query::server::rules_to_bytes;
+    /// use valve_server_query::Rules;
+    ///
+    /// let rules = Rules::from(vec![("sv_cheats".to_string(), "0".to_string())]);
+    /// let payload = rules_to_bytes(&rules);
+    /// assert_eq!(payload[..3], [0x45, 0x01, 0x00]);
+    /// assert_eq!(parse_rules_consumed(&payload[1..]).unwrap(), (rules, payload.len() - 1));
+    /// ```
+    pub fn rules_to_bytes(rules: &Rules) -> Vec<u8> {
+        let pairs: Vec<(&str, &str)> = rules
+            .duplicates
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(
+                rules
+                    .map
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            )
+            .take(Short::MAX as usize)
+            .collect();
+
+        let mut payload = vec![ResponseKind::Rules.as_byte()];
+        put_short(&mut payload, pairs.len() as Short);
+        for (name, value) in pairs {
+            put_cstring(&mut payload, name);
+            put_cstring(&mut payload, value);
+        }
+        payload
+    }
+
+    /// Default read and write timeout of the socket.
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+    /// Default number of challenges answered before a query gives up.
+    const DEFAULT_MAX_CHALLENGES: u8 = 3;
+    /// Default maximum size of a reassembled split response, in bytes.
+    const DEFAULT_MAX_PAYLOAD_BYTES: usize = 128 * 1024;
+    /// Default whole-response deadline of split response reassembly, as a multiple of the read
+    /// timeout.
+    const DEFAULT_REASSEMBLY_TIMEOUT_FACTOR: u32 = 3;
+    /// Longest a read waits before checking the cancel flag again.
+    const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+    /// Default time a received challenge is reused for later queries.
+    const DEFAULT_CHALLENGE_LIFETIME: Duration = Duration::from_secs(30);
+
+    /// Map a socket error, reporting the server unreachable when the socket was told so.
+    ///
+    /// An ICMP port unreachable reply surfaces as `ConnectionRefused` on connected sockets, and
+    /// as `ConnectionReset` on Windows.
+    fn socket_error(e: io::Error) -> QueryError {
+        match e.kind() {
+            io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset => {
+                QueryError::ServerUnreachable(e)
+            }
+            _ => QueryError::Io(e),
+        }
+    }
+
+    /// Engine the server runs, which determines the layout of split packet headers.
+    ///
+    /// Ref: <https://developer.valvesoftware.com/wiki/Server_queries#Multi-packet_Response_Format>
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+    pub enum Engine {
+        /// Source engine (Orange Box and later). Split packets carry a 2 byte size field.
+        #[default]
+        Source,
+        /// Source engine prior to the Orange Box, whose split packets have no size field.
+        ///
+        /// Used by AppIDs 215, 17550, 17700 and 240 when their protocol version is 7.
+        SourcePreOrangeBox,
+        /// GoldSource engine, whose split packets pack the packet number and total into a
+        /// single byte.
+        GoldSource,
+    }
+
+    impl Engine {
+        /// Number of bytes before the payload in each split packet.
+        pub fn split_header_size(&self) -> usize {
+            match self {
+                // Header, ID, Total, Number, Size
+                Self::Source => 4 + 4 + 1 + 1 + 2,
+                // Header, ID, Total, Number
+                Self::SourcePreOrangeBox => 4 + 4 + 1 + 1,
+                // Header, ID, Number and Total
+                Self::GoldSource => 4 + 4 + 1,
+            }
+        }
+    }
+
+    /// Represents a game server running a Steam game.
+    ///
+    /// ```no_run
+    /// use valve_server_query::Server;
+    ///
+    /// let server = Server::new("127.0.0.1:12345").expect("Connect to dedicated server running Valve game");
+    ///
+    /// let info = server.info().expect("Get general server information");
+    /// let players = server.players().expect("Get server player information");
+    /// let rules = server.rules().expect("Get server rules");
+    /// ```
+    ///
+    /// A `Server` keeps its socket for its whole lifetime, and can be reused for any number of
+    /// queries, such as polling [`Server::info`] on a timer. Each query stands on its own: a
+    /// failed one, such as [`QueryError::Timeout`] or [`QueryError::ServerUnreachable`] while
+    /// the server restarts, leaves the `Server` usable for the next.
+    #[derive(Debug)]
+    pub struct Server {
+        transport: Box<dyn Transport>,
+        addr: SocketAddr,
+        engine: Engine,
+        allow_source_port_mismatch: bool,
+        max_challenges: u8,
+        challenge_lifetime: Duration,
+        last_challenge: Mutex<Option<([u8; 4], Instant)>>,
+        max_payload_bytes: usize,
+        reassembly_timeout: Option<Duration>,
+        recv_buffer_size: usize,
+        cancel: Option<Arc<AtomicBool>>,
+        options: ParseOptions,
+        info_poll: InfoPoll,
+    }
+
+    impl Server {
+        /// Connect to the server at `url`, either `ip:port` or `hostname:port`.
+        ///
+        /// Uses the default [`ServerBuilder`] settings.
+        pub fn new(url: &str) -> Result<Self, QueryError> {
+            ServerBuilder::new(url).build()
+        }
+
+        /// Configure a server before connecting to it.
+        pub fn builder(url: &str) -> ServerBuilder {
+            ServerBuilder::new(url)
+        }
+
+        /// Query the server at `addr` over an existing socket, such as one bound to a fixed
+        /// source port.
+        ///
+        /// The socket is adopted as-is, so its binding and options (including timeouts) are up
+        /// to the caller.
+        pub fn with_socket(addr: SocketAddr, socket: UdpSocket) -> Self {
+            Self::with_transport(addr, socket)
+        }
+
+        /// Query the server at `addr` over any [`Transport`], such as a
+        /// [`MockTransport`](crate::MockTransport) in tests.
+        pub fn with_transport(addr: SocketAddr, transport: impl Transport + 'static) -> Self {
+            ServerBuilder::new(&addr.to_string()).assemble(addr, Box::new(transport))
+        }
+
+        /// Query the server at `target` through the SOCKS5 proxy at `proxy`, for networks that
+        /// only let traffic out through a proxy.
+        ///
+        /// Uses the default [`ServerBuilder`] settings, see [`ServerBuilder::build_via_proxy`].
+        #[cfg(feature = "socks5")]
+        pub fn via_proxy(proxy: SocketAddr, target: SocketAddr) -> Result<Self, QueryError> {
+            ServerBuilder::new(&target.to_string()).build_via_proxy(proxy)
+        }
+
+        /// Address queried, as resolved from the hostname the server was created with.
+        pub fn addr(&self) -> SocketAddr {
+            self.addr
+        }
+    }
+
+    /// Parse `ip:port` or `hostname:port` and connect to the server, like [`Server::new`].
+    ///
+    /// ```no_run
+    /// use valve_server_query::Server;
+    ///
+    /// let server: Server = "127.0.0.1:27015".parse().expect("Connect to server");
+    /// ```
+    impl FromStr for Server {
+        type Err = QueryError;
+
+        fn from_str(url: &str) -> Result<Self, Self::Err> {
+            Self::new(url)
+        }
+    }
+
+    /// Preferred address family when a hostname resolves to both IPv4 and IPv6 addresses.
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+    pub enum Prefer {
+        /// Use the first resolved address.
+        #[default]
+        Any,
+        /// Use the first resolved IPv4 address.
+        V4,
+        /// Use the first resolved IPv6 address.
+        V6,
+    }
+
+    impl Prefer {
+        fn matches(&self, addr: &SocketAddr) -> bool {
+            match self {
+                Self::Any => true,
+                Self::V4 => addr.is_ipv4(),
+                Self::V6 => addr.is_ipv6(),
+            }
+        }
+    }
+
+    /// Builder for a [`Server`].
+    ///
+    /// ```no_run
+    /// use valve_server_query::{Prefer, ServerBuilder};
+    ///
+    /// let server = ServerBuilder::new("localhost:27015")
+    ///     .address_family_preference(Prefer::V4)
+    ///     .build()
+    ///     .expect("Connect to dedicated server running Valve game");
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct ServerBuilder {
+        url: String,
+        prefer: Prefer,
+        engine: Engine,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+        allow_source_port_mismatch: bool,
+        max_challenges: u8,
+        challenge_lifetime: Duration,
+        max_payload_bytes: usize,
+        reassembly_timeout: Option<Duration>,
+        recv_buffer_size: usize,
+        cancel: Option<Arc<AtomicBool>>,
+        options: ParseOptions,
+    }
+
+    impl ServerBuilder {
+        pub fn new(url: &str) -> Self {
+            Self {
+                url: url.to_string(),
+                prefer: Prefer::default(),
+                engine: Engine::default(),
+                read_timeout: Some(DEFAULT_TIMEOUT),
+                write_timeout: Some(DEFAULT_TIMEOUT),
+                allow_source_port_mismatch: false,
+                max_challenges: DEFAULT_MAX_CHALLENGES,
+                challenge_lifetime: DEFAULT_CHALLENGE_LIFETIME,
+                max_payload_bytes: DEFAULT_MAX_PAYLOAD_BYTES,
+                reassembly_timeout: None,
+                recv_buffer_size: PACKET_SIZE,
+                cancel: None,
+                options: ParseOptions::default(),
+            }
+        }
+
+        /// Address family to use when the hostname resolves to several addresses.
+        pub fn address_family_preference(mut self, prefer: Prefer) -> Self {
+            self.prefer = prefer;
+            self
+        }
+        /// Engine the server runs, used to parse split packet headers.
+        pub fn engine(mut self, engine: Engine) -> Self {
+            self.engine = engine;
+            self
+        }
+        /// How long a query waits for each response datagram. Defaults to 1 second.
+        ///
+        /// `None` blocks forever. See [`Server::set_read_timeout`].
+        pub fn read_timeout(mut self, duration: Option<Duration>) -> Self {
+            self.read_timeout = duration;
+            self
+        }
+        /// How long sending a request may block. Defaults to 1 second.
+        ///
+        /// `None` blocks forever. See [`Server::set_write_timeout`].
+        pub fn write_timeout(mut self, duration: Option<Duration>) -> Self {
+            self.write_timeout = duration;
+            self
+        }
+        /// Accept replies from the server's IP address on any port, rather than only from the
+        /// queried address and port.
+        ///
+        /// Replies from other addresses are always discarded, so that a host which learns the
+        /// local port cannot inject responses. Enable this only for NAT setups where replies
+        /// legitimately come from a different port.
+        pub fn allow_source_port_mismatch(mut self, allow: bool) -> Self {
+            self.allow_source_port_mismatch = allow;
+            self
+        }
+        /// Number of challenges a query answers before giving up. Defaults to 3.
+        ///
+        /// Servers may invalidate a challenge at any time and reply to a challenged request with
+        /// a new challenge, which is answered by resending the request with it.
+        pub fn max_challenges(mut self, max_challenges: u8) -> Self {
+            self.max_challenges = max_challenges;
+            self
+        }
+        /// How long a received challenge is reused for later queries. Defaults to 30 seconds.
+        ///
+        /// While the last challenge is this fresh, queries include it up front instead of
+        /// first asking the server for one, saving a round trip. A server that has since
+        /// rotated its challenge replies with a new one, which is answered as usual. A zero
+        /// duration disables reuse.
+        pub fn challenge_lifetime(mut self, lifetime: Duration) -> Self {
+            self.challenge_lifetime = lifetime;
+            self
+        }
+        /// Whether the server runs The Ship (AppID 2400), whose player records carry extra
+        /// fields. See [`Server::set_the_ship`].
+        pub fn the_ship(mut self, the_ship: bool) -> Self {
+            self.options.the_ship = the_ship;
+            self
+        }
+        /// Maximum size in bytes of a reassembled split response. Defaults to 128 KiB.
+        ///
+        /// A split response can claim up to 255 packets, so this bounds the memory a server can
+        /// make a query hold. Reassembly stops with [`QueryError::PayloadTooLarge`] once the
+        /// packets received exceed it.
+        pub fn max_payload_bytes(mut self, max_payload_bytes: usize) -> Self {
+            self.max_payload_bytes = max_payload_bytes;
+            self
+        }
+        /// How long reassembling a split response may take as a whole. Defaults to 3 times the
+        /// read timeout.
+        ///
+        /// Each packet is still awaited for at most the read timeout. `None` restores the
+        /// default, which is unbounded when the read timeout is.
+        pub fn reassembly_timeout(mut self, duration: Option<Duration>) -> Self {
+            self.reassembly_timeout = duration;
+            self
+        }
+        /// Flag that cancels queries when set, such as when an application shuts down.
+        ///
+        /// A query in progress, including one waiting on a lost split packet, notices the flag
+        /// within 50 milliseconds and fails with [`QueryError::Cancelled`], as does every query
+        /// made while the flag stays set.
+        pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+            self.cancel = Some(flag);
+            self
+        }
+        /// Size in bytes of the buffer each datagram is received into. Defaults to 1400, the
+        /// largest packet Valve's servers send.
+        ///
+        /// Raise it for servers sending larger packets, such as on networks with jumbo frames.
+        /// A datagram filling the whole buffer may have been cut off, and fails the query with
+        /// [`QueryError::ResponseTruncated`].
+        pub fn recv_buffer_size(mut self, recv_buffer_size: usize) -> Self {
+            self.recv_buffer_size = recv_buffer_size;
+            self
+        }
+        /// How the strings of responses are decoded. Defaults to [`Encoding::Utf8Lossy`].
+        ///
+        /// See [`Server::set_encoding`].
+        pub fn encoding(mut self, encoding: Encoding) -> Self {
+            self.options.encoding = encoding;
+            self
+        }
+        /// Maximum length in bytes of a string in a response. Defaults to 4 KiB.
+        ///
+        /// Together with the rule and player limits, this bounds the work a corrupted or
+        /// malicious response can cause when scanning untrusted hosts. A longer string fails the
+        /// query with [`QueryError::StringTooLong`].
+        pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+            self.options.max_string_len = max_string_len;
+            self
+        }
+        /// Maximum number of rules in an A2S_RULES response. Defaults to 4096.
+        ///
+        /// A response declaring or containing more fails with [`QueryError::TooManyEntries`].
+        pub fn max_rules(mut self, max_rules: usize) -> Self {
+            self.options.max_rules = max_rules;
+            self
+        }
+        /// Maximum number of players in an A2S_PLAYER response. Defaults to 255, the most the
+        /// response can declare.
+        ///
+        /// A response declaring more fails with [`QueryError::TooManyEntries`].
+        pub fn max_players(mut self, max_players: usize) -> Self {
+            self.options.max_players = max_players;
+            self
+        }
+
+        /// Resolve the address and bind a socket to query it from.
+        pub fn build(self) -> Result<Server, QueryError> {
+            let addr = self.resolve()?;
+
+            let unspecified = match addr {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            let socket = UdpSocket::bind((unspecified, 0))?;
+
+            // Socket Settings
+            socket.set_read_timeout(self.read_timeout)?;
+            socket.set_write_timeout(self.write_timeout)?;
+
+            Ok(self.assemble(addr, Box::new(socket)))
+        }
+
+        /// Resolve the address and query it through the SOCKS5 proxy at `proxy`, see
+        /// [`Socks5Transport`](crate::Socks5Transport).
+        ///
+        /// The read timeout of this builder also bounds each step of the proxy handshake.
+        #[cfg(feature = "socks5")]
+        pub fn build_via_proxy(self, proxy: SocketAddr) -> Result<Server, QueryError> {
+            let addr = self.resolve()?;
+
+            let transport = crate::transport::Socks5Transport::connect(
+                proxy,
+                self.read_timeout.unwrap_or(DEFAULT_TIMEOUT),
+            )?;
+            transport.set_read_timeout(self.read_timeout)?;
+            transport.set_write_timeout(self.write_timeout)?;
+
+            Ok(self.assemble(addr, Box::new(transport)))
+        }
+
+        /// Resolve the address and query it over an existing socket.
+        ///
+        /// The socket is adopted as-is: its binding and options, including its timeouts, are left
+        /// untouched, so the read and write timeouts of this builder are not applied.
+        pub fn build_with_socket(self, socket: UdpSocket) -> Result<Server, QueryError> {
+            self.build_with_transport(socket)
+        }
+
+        /// Resolve the address and query it over any [`Transport`], adopted as-is like a socket
+        /// passed to [`ServerBuilder::build_with_socket`].
+        pub fn build_with_transport(
+            self,
+            transport: impl Transport + 'static,
+        ) -> Result<Server, QueryError> {
+            let addr = self.resolve()?;
+            Ok(self.assemble(addr, Box::new(transport)))
+        }
+
+        fn resolve(&self) -> Result<SocketAddr, QueryError> {
+            self.url
+                .to_socket_addrs()?
+                .find(|addr| self.prefer.matches(addr))
+                .ok_or_else(|| QueryError::NoMatchingAddress(self.url.clone()))
+        }
+
+        fn assemble(self, addr: SocketAddr, transport: Box<dyn Transport>) -> Server {
+            Server {
+                addr,
+                transport,
+                engine: self.engine,
+                allow_source_port_mismatch: self.allow_source_port_mismatch,
+                max_challenges: self.max_challenges,
+                challenge_lifetime: self.challenge_lifetime,
+                last_challenge: Mutex::new(None),
+                max_payload_bytes: self.max_payload_bytes,
+                reassembly_timeout: self.reassembly_timeout,
+                recv_buffer_size: self.recv_buffer_size,
+                cancel: self.cancel,
+                options: self.options,
+                info_poll: InfoPoll::default(),
+            }
+        }
+    }
+
+    /// Socket Settings
+    impl Server {
+        /// Set how long a query waits for each response datagram.
+        ///
+        /// `None` blocks forever, so a query against a server that never answers never returns.
+        /// An error is returned if a zero duration is passed.
+        pub fn set_read_timeout(
+            &mut self,
+            duration: Option<Duration>,
+        ) -> Result<(), Box<dyn Error>> {
+            self.transport.set_read_timeout(duration)?;
+            Ok(())
+        }
+        /// Set how long sending a request may block.
+        ///
+        /// `None` blocks forever. An error is returned if a zero duration is passed.
+        pub fn set_write_timeout(
+            &mut self,
+            duration: Option<Duration>,
+        ) -> Result<(), Box<dyn Error>> {
+            self.transport.set_write_timeout(duration)?;
+            Ok(())
+        }
+        /// Set the flag that cancels queries when set, see [`ServerBuilder::cancel_flag`].
+        /// `None` removes it.
+        pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+            self.cancel = flag;
+        }
+    }
+
+    /// Protocol Settings
+    impl Server {
+        /// Engine the server runs, used to parse split packet headers.
+        pub fn engine(&self) -> Engine {
+            self.engine
+        }
+        /// Set the engine the server runs, for servers whose split packets are not in the
+        /// default [`Engine::Source`] layout.
+        pub fn set_engine(&mut self, engine: Engine) {
+            self.engine = engine;
+        }
+        /// Whether the server is treated as running The Ship.
+        pub fn the_ship(&self) -> bool {
+            self.options.the_ship
+        }
+        /// Set whether the server runs The Ship (AppID 2400), whose player records carry the
+        /// deaths and money of each player. Check [`Info::steam_app_id`] to detect it.
+        pub fn set_the_ship(&mut self, the_ship: bool) {
+            self.options.the_ship = the_ship;
+        }
+        /// How the strings of responses are decoded.
+        pub fn encoding(&self) -> Encoding {
+            self.options.encoding
+        }
+        /// Set how the strings of responses are decoded, such as [`Encoding::Utf8Strict`] to
+        /// reject invalid names, or Windows-1252 for legacy GoldSource servers with the
+        /// `encoding` feature.
+        pub fn set_encoding(&mut self, encoding: Encoding) {
+            self.options.encoding = encoding;
+        }
+    }
+
+    impl Server {
+        /// Receive a single datagram from the server into `buffer`.
+        ///
+        /// Datagrams from any other address are discarded, and waiting continues until the read
+        /// timeout, reported as [`QueryError::Timeout`]. A datagram that fills the whole buffer
+        /// is treated as truncated, since the socket silently discards anything that does not
+        /// fit.
+        ///
+        /// With a cancel flag, the wait is split into short reads to check the flag between
+        /// them.
+        fn recv(&self, buffer: &mut [u8]) -> Result<usize, QueryError> {
+            let timeout = self.transport.read_timeout()?;
+            let received = self.recv_within(buffer, timeout);
+            if self.cancel.is_some() {
+                self.transport.set_read_timeout(timeout)?;
+            }
+            received
+        }
+
+        fn recv_within(
+            &self,
+            buffer: &mut [u8],
+            timeout: Option<Duration>,
+        ) -> Result<usize, QueryError> {
+            let started = Instant::now();
+            loop {
+                self.check_cancelled()?;
+                if self.cancel.is_some() {
+                    let remaining =
+                        timeout.map(|timeout| timeout.saturating_sub(started.elapsed()));
+                    if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                        return Err(QueryError::Timeout);
+                    }
+                    let wait = remaining.map_or(CANCEL_CHECK_INTERVAL, |remaining| {
+                        remaining.min(CANCEL_CHECK_INTERVAL)
+                    });
+                    self.transport.set_read_timeout(Some(wait))?;
+                }
+
+                let (bytes_returned, source) = match self.recv_from(buffer) {
+                    Err(QueryError::Timeout) if self.cancel.is_some() => continue,
+                    received => received?,
+                };
+
+                if !self.is_from_server(&source) {
+                    if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                        return Err(QueryError::Timeout);
+                    }
+                    continue;
+                }
+
+                if bytes_returned >= buffer.len() {
+                    return Err(QueryError::ResponseTruncated {
+                        buffer_size: buffer.len(),
+                    });
+                }
+                return Ok(bytes_returned);
+            }
+        }
+
+        /// Send a single datagram to the server.
+        fn send(&self, request: &[u8]) -> Result<(), QueryError> {
+            self.transport
+                .send_to(request, self.addr)
+                .map_err(socket_error)?;
+            Ok(())
+        }
+
+        /// Receive a single datagram from any source, mapping the socket timing out to
+        /// [`QueryError::Timeout`].
+        ///
+        /// A read timeout is reported as `WouldBlock` or `TimedOut` depending on the platform,
+        /// and as `WouldBlock` when no datagram is queued in non-blocking mode.
+        fn recv_from(&self, buffer: &mut [u8]) -> Result<(usize, SocketAddr), QueryError> {
+            self.transport
+                .recv_from(buffer)
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => QueryError::Timeout,
+                    _ => socket_error(e),
+                })
+        }
+
+        /// Fail with [`QueryError::Cancelled`] if the cancel flag is set.
+        fn check_cancelled(&self) -> Result<(), QueryError> {
+            match &self.cancel {
+                Some(cancel) if cancel.load(Ordering::Relaxed) => Err(QueryError::Cancelled),
+                _ => Ok(()),
+            }
+        }
+
+        /// Buffer to receive a datagram into, of the configured receive buffer size.
+        fn buffer(&self) -> Vec<u8> {
+            vec![0; self.recv_buffer_size]
+        }
+
+        /// Discard any datagrams already queued on the socket, such as late replies to an
+        /// earlier query that timed out, so they are not mistaken for the reply to the next
+        /// request.
+        fn drain(&self) -> Result<(), QueryError> {
+            let mut buffer = self.buffer();
+            self.transport.set_nonblocking(true)?;
+            let drained = loop {
+                match self.recv_from(&mut buffer) {
+                    Ok(_) => continue,
+                    Err(QueryError::Timeout) => break Ok(()),
+                    Err(e) => break Err(e),
+                }
+            };
+            self.transport.set_nonblocking(false)?;
+            drained
+        }
+
+        /// Whether a datagram from `source` should be accepted as a reply from the server.
+        fn is_from_server(&self, source: &SocketAddr) -> bool {
+            if self.allow_source_port_mismatch {
+                source.ip() == self.addr.ip()
+            } else {
+                *source == self.addr
+            }
+        }
+    }
+
+    /// How the response to a query arrived, for diagnosing flaky servers.
+    ///
+    /// ```no_run
+    /// use valve_server_query::Server;
+    ///
+    /// let server = Server::new("127.0.0.1:27015").expect("Connect to server");
+    /// let (info, meta) = server.info_with_meta().expect("Get general server information");
+    /// println!("{}: {} packet(s)", info.name(), meta.fragments);
+    /// ```
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+    pub struct QueryMeta {
+        /// Number of packets the response arrived in, 1 unless it was split.
+        pub fragments: u8,
+        /// Whether the server flagged the split response as compressed.
+        ///
+        /// Compressed responses are not decompressed yet and fail with
+        /// [`QueryError::CompressedResponse`], so this is `false` for every query that succeeds.
+        pub compressed: bool,
+        /// Size in bytes of the largest packet of the response, including its headers.
+        ///
+        /// Close to the receive buffer size, it warns of packets at risk of being cut off.
+        pub largest_packet: usize,
+        /// Number of challenges answered before the response arrived.
+        pub challenges: u8,
+    }
+
+    /// Split packets of a multi-packet response, collected until every packet has arrived.
+    ///
+    /// Every packet must carry the answer ID of the first packet received; packets from any
+    /// other response (such as a stale reply to an earlier query) are ignored.
+    #[derive(Debug)]
+    struct SplitResponse {
+        answer_id: u32,
+        total: Byte,
+        compressed: bool,
+        engine: Engine,
+        packets: HashMap<Byte, Vec<u8>>,
+        /// Combined size of the packets collected so far.
+        size: usize,
+        /// Size of the largest packet collected, including its split header.
+        largest_packet: usize,
+        max_payload_bytes: usize,
+    }
+
+    impl SplitResponse {
+        /// Start collecting a split response from the first split packet received.
+        fn new(first: &[u8], engine: Engine, max_payload_bytes: usize) -> Result<Self, QueryError> {
+            let header = SplitPacketHeader::parse(first, engine)?;
+            let mut response = Self {
+                answer_id: header.id,
+                total: header.total,
+                compressed: header.compressed,
+                engine,
+                packets: HashMap::new(),
+                size: 0,
+                largest_packet: 0,
+                max_payload_bytes,
+            };
+            response.insert(first)?;
+            Ok(response)
+        }
+
+        /// Add a split packet, including its split header.
+        ///
+        /// Returns `false` if the datagram is not a split packet of this response and was
+        /// ignored, and an error if it takes the payload beyond the maximum size.
+        fn insert(&mut self, packet: &[u8]) -> Result<bool, QueryError> {
+            let header = match SplitPacketHeader::parse(packet, self.engine) {
+                Ok(header) => header,
+                Err(_) => return Ok(false),
+            };
+
+            // id starts at 0
+            // udp means they don't have to be in order
+            let packet_id = header.number;
+            if header.id != self.answer_id || packet_id >= self.total {
+                return Ok(false);
+            }
+            let data = &packet[header.header_len..];
+            let replaced = self.packets.get(&packet_id).map_or(0, Vec::len);
+            let size = self.size - replaced + data.len();
+            if size > self.max_payload_bytes {
+                return Err(QueryError::PayloadTooLarge {
+                    limit: self.max_payload_bytes,
+                });
+            }
+            self.size = size;
+            self.largest_packet = self.largest_packet.max(packet.len());
+            self.packets.insert(packet_id, data.to_vec());
+            trace!(
+                "received split packet {} of {} ({} bytes)",
+                packet_id + 1,
+                self.total,
+                data.len()
+            );
+            Ok(true)
+        }
+
+        /// Give up on the missing packets: with `partial` set, reassemble the packets that
+        /// arrived in sequence from the first, otherwise report the response incomplete.
+        fn give_up(mut self, partial: bool) -> Result<Vec<u8>, QueryError> {
+            debug!(
+                "split response incomplete: {} of {} packets received",
+                self.packets.len(),
+                self.total
+            );
+            if !partial {
+                return Err(self.incomplete());
+            }
+            let received = (0..self.total)
+                .take_while(|number| self.packets.contains_key(number))
+                .count() as Byte;
+            self.packets.retain(|number, _| *number < received);
+            self.into_payload()
+        }
+
+        fn is_complete(&self) -> bool {
+            self.packets.len() >= self.total as usize
+        }
+
+        /// Metadata of the packets collected so far.
+        fn meta(&self) -> QueryMeta {
+            QueryMeta {
+                fragments: self.packets.len() as u8,
+                compressed: self.compressed,
+                largest_packet: self.largest_packet,
+                challenges: 0,
+            }
+        }
+
+        /// Error for a response that stopped arriving before it was complete.
+        fn incomplete(&self) -> QueryError {
+            QueryError::IncompleteResponse {
+                received: self.packets.len() as u8,
+                expected: self.total,
+                missing: (0..self.total)
+                    .filter(|number| !self.packets.contains_key(number))
+                    .collect(),
+                assembled: self.size,
+            }
+        }
+
+        /// Reassemble the payload, removing the simple response header the reassembled response
+        /// starts with, so it begins at the response type byte.
+        ///
+        /// Fails with [`QueryError::CompressedResponse`] if the server flagged the response as
+        /// compressed, since it is not decompressed.
+        fn into_payload(self) -> Result<Vec<u8>, QueryError> {
+            if self.compressed {
+                return Err(QueryError::CompressedResponse);
+            }
+            // Sort and Collect all packet data
+            let mut v: Vec<(u8, Vec<u8>)> = self.packets.into_iter().collect();
+            v.sort_by_key(|i| i.0);
+            let mut payload = Vec::with_capacity(self.size);
+            for (_, bytes) in v {
+                payload.extend(bytes);
+            }
+
+            if payload.starts_with(&SIMPLE_RESPONSE_HEADER) {
+                payload.drain(..SIMPLE_RESPONSE_HEADER.len());
+            }
+            debug!("reassembled split response of {} bytes", payload.len());
+            Ok(payload)
+        }
+    }
+
+    /// Split Packet Reassembly
+    impl Server {
+        /// Receive the remaining packets of a split response and reassemble the payload.
+        ///
+        /// `first` is the split packet that has already been received.
+        ///
+        /// Datagrams that are not split packets of this response are skipped, as are repeats
+        /// of a packet already received. They do not extend the wait, so the response is
+        /// reported incomplete once no packet of it has arrived for the read timeout, or once
+        /// the whole reassembly deadline passes. With `partial` set, the packets received in
+        /// sequence are returned instead.
+        fn reassemble(
+            &self,
+            first: &[u8],
+            partial: bool,
+        ) -> Result<(Vec<u8>, QueryMeta), QueryError> {
+            let read_timeout = self.transport.read_timeout()?;
+            let result = self.reassemble_within(first, read_timeout, partial);
+            self.transport.set_read_timeout(read_timeout)?;
+            result
+        }
+
+        /// Reassemble with the socket read timeout narrowed to the time left before each
+        /// deadline.
+        fn reassemble_within(
+            &self,
+            first: &[u8],
+            read_timeout: Option<Duration>,
+            partial: bool,
+        ) -> Result<(Vec<u8>, QueryMeta), QueryError> {
+            let mut response = SplitResponse::new(first, self.engine, self.max_payload_bytes)?;
+            let started = Instant::now();
+            let whole_timeout = self.reassembly_timeout.or_else(|| {
+                read_timeout.map(|timeout| timeout * DEFAULT_REASSEMBLY_TIMEOUT_FACTOR)
+            });
+            let mut last_packet = started;
+
+            // Get the remaining packet data.
+            let mut buffer = self.buffer();
+            while !response.is_complete() {
+                let remaining = [
+                    read_timeout.map(|timeout| timeout.saturating_sub(last_packet.elapsed())),
+                    whole_timeout.map(|timeout| timeout.saturating_sub(started.elapsed())),
+                ]
+                .into_iter()
+                .flatten()
+                .min();
+                if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                    let meta = response.meta();
+                    return Ok((response.give_up(partial)?, meta));
+                }
+                self.transport.set_read_timeout(remaining)?;
+
+                let bytes_returned = match self.recv(&mut buffer) {
+                    Ok(bytes_returned) => bytes_returned,
+                    Err(QueryError::Timeout) => {
+                        let meta = response.meta();
+                        return Ok((response.give_up(partial)?, meta));
+                    }
+                    Err(e) => return Err(e),
+                };
+                if response.insert(&buffer[..bytes_returned])? {
+                    last_packet = Instant::now();
+                }
+            }
+
+            let meta = response.meta();
+            Ok((response.into_payload()?, meta))
+        }
+    }
+
+    /// Challenge Handling
+    impl Server {
+        /// The last challenge received, if it is younger than the configured lifetime.
+        fn fresh_challenge(&self) -> Option<[u8; 4]> {
+            let last_challenge = self.last_challenge.lock().ok()?;
+            last_challenge
+                .filter(|(_, received)| received.elapsed() < self.challenge_lifetime)
+                .map(|(challenge, _)| challenge)
+        }
+
+        /// Remember a challenge received from the server, to reuse in later queries.
+        fn remember_challenge(&self, challenge: [u8; 4]) {
+            if let Ok(mut last_challenge) = self.last_challenge.lock() {
+                *last_challenge = Some((challenge, Instant::now()));
+            }
+        }
+
+        /// Request a challenge number with A2S_SERVERQUERY_GETCHALLENGE.
+        ///
+        /// Some older GoldSource and early Source servers only hand out challenges in response
+        /// to this dedicated request.
+        pub fn get_challenge(&self) -> Result<[u8; 4], QueryError> {
+            let request = request::get_challenge();
+            self.drain()?;
+            self.send(&request)?;
+
+            let mut buffer = self.buffer();
+            let bytes_returned = self.recv(&mut buffer)?;
+            let packet = &buffer[..bytes_returned];
+
+            let challenge = parse_challenge(packet)?;
+            debug!("challenge received from {}: {:02X?}", self.addr, challenge);
+            self.remember_challenge(challenge);
+            Ok(challenge)
+        }
+
+        /// Request a challenge number with A2S_SERVERQUERY_GETCHALLENGE, as a little-endian
+        /// `i32`.
+        ///
+        /// Same as [`Server::get_challenge`], for crafting requests by hand.
+        pub fn challenge(&self) -> Result<Long, QueryError> {
+            Ok(Long::from_le_bytes(self.get_challenge()?))
+        }
+
+        /// Send a request and receive the response, answering challenges along the way.
+        ///
+        /// `build` creates the request to send, given the challenge number to include, or
+        /// `None` for the initial request. The initial request includes the last challenge
+        /// received instead while it is fresh. Whenever the server replies with a challenge,
+        /// the request is rebuilt with it and resent, up to the configured maximum number of
+        /// challenges. The returned payload begins at the response type byte, and is returned
+        /// along with how it arrived.
+        ///
+        /// With `legacy_header` set to the expected response header, a reply to the initial
+        /// request that times out or has any other header falls back to requesting a challenge
+        /// with [`Server::get_challenge`], as legacy servers require.
+        fn query<F>(
+            &self,
+            build: F,
+            legacy_header: Option<ResponseKind>,
+        ) -> Result<(Vec<u8>, QueryMeta), QueryError>
+        where
+            F: Fn(Option<[u8; 4]>) -> Vec<u8>,
+        {
+            self.query_partial(build, legacy_header, false)
+        }
+
+        /// Like [`Server::query`], returning the packets of an incomplete split response that
+        /// arrived in sequence when `partial` is set.
+        fn query_partial<F>(
+            &self,
+            build: F,
+            legacy_header: Option<ResponseKind>,
+            partial: bool,
+        ) -> Result<(Vec<u8>, QueryMeta), QueryError>
+        where
+            F: Fn(Option<[u8; 4]>) -> Vec<u8>,
+        {
+            let mut buffer = self.buffer();
+            let mut request = build(self.fresh_challenge());
+            let mut challenges = 0;
+
+            loop {
+                self.drain()?;
+                self.send(&request)?;
+                trace!("sent request to {}: {:02X?}", self.addr, request);
+                let received = self.recv(&mut buffer);
+                if let Ok(bytes_returned) = received {
+                    check_packet_len(&buffer[..bytes_returned])?;
+                }
+
+                if let Some(expected) = legacy_header.filter(|_| challenges == 0) {
+                    let fallback = match &received {
+                        Ok(bytes_returned) => {
+                            let packet = &buffer[..*bytes_returned];
+                            packet.starts_with(&SIMPLE_RESPONSE_HEADER)
+                                && response_kind(packet) != Some(ResponseKind::Challenge)
+                                && response_kind(packet) != Some(expected)
+                        }
+                        Err(QueryError::Timeout) => true,
+                        Err(_) => false,
+                    };
+                    if fallback {
+                        debug!("no reply to the initial request, requesting a challenge");
+                        challenges += 1;
+                        request = build(Some(self.get_challenge()?));
+                        continue;
+                    }
+                }
+
+                let packet = &buffer[..received?];
+
+                if packet.starts_with(&SIMPLE_RESPONSE_HEADER) {
+                    if response_kind(packet) != Some(ResponseKind::Challenge) {
+                        let meta = QueryMeta {
+                            fragments: 1,
+                            compressed: false,
+                            largest_packet: packet.len(),
+                            challenges,
+                        };
+                        return Ok((packet[4..].to_vec(), meta));
+                    }
+
+                    // Challenge Received
+                    if challenges == self.max_challenges {
+                        return Err(QueryError::TooManyChallenges(challenges + 1));
+                    }
+                    challenges += 1;
+                    let challenge = parse_challenge(packet)?;
+                    debug!("challenge received from {}: {:02X?}", self.addr, challenge);
+                    self.remember_challenge(challenge);
+                    request = build(Some(challenge));
+                } else if packet.starts_with(&MULTI_PACKET_RESPONSE_HEADER) {
+                    debug!("split response from {}", self.addr);
+                    let (payload, meta) = self.reassemble(packet, partial)?;
+                    return Ok((payload, QueryMeta { challenges, ..meta }));
+                } else {
+                    return Err(QueryError::MalformedPacket(
+                        "unknown packet header".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    // A2S_INFO Implementation
+    impl Server {
+        /// Query general information about the server.
+        ///
+        /// Servers updated against reflection attacks reply with a challenge instead, which is
+        /// appended to the request and sent again, whatever the length of the challenge
+        /// response.
+        pub fn info(&self) -> Result<Info, QueryError> {
+            Ok(self.info_with_meta()?.0)
+        }
+
+        /// Query A2S_INFO like [`Server::info`], along with how the response arrived.
+        pub fn info_with_meta(&self) -> Result<(Info, QueryMeta), QueryError> {
+            let (payload, meta) = self.query(request::info, None)?;
+
+            let info = self.options.info_from_payload(&payload)?;
+            Ok((info, meta))
+        }
+
+        /// Query A2S_INFO like [`Server::info`], returning the payload unparsed, as for
+        /// decoding data a plugin appends after the known fields.
+        ///
+        /// The payload starts at the header byte, without the simple response header, and
+        /// parses with [`Info::from_bytes`]. Split responses are reassembled.
+        pub fn info_raw(&self) -> Result<Vec<u8>, QueryError> {
+            Ok(self.query(request::info, None)?.0)
+        }
+
+        /// Query A2S_INFO with a single request, for servers known not to require a challenge.
+        ///
+        /// Saves the round trip [`Server::info`] may spend on a challenge, but fails with
+        /// [`QueryError::ChallengeRequired`] if the server replies with one anyway.
+        pub fn info_no_challenge(&self) -> Result<Info, QueryError> {
+            self.drain()?;
+            self.send(&request::info(None))?;
+
+            let mut buffer = self.buffer();
+            let bytes_returned = self.recv(&mut buffer)?;
+            let packet = &buffer[..bytes_returned];
+            check_packet_len(packet)?;
+
+            let payload = if packet.starts_with(&MULTI_PACKET_RESPONSE_HEADER) {
+                self.reassemble(packet, false)?.0
+            } else if packet.starts_with(&SIMPLE_RESPONSE_HEADER) {
+                if response_kind(packet) == Some(ResponseKind::Challenge) {
+                    return Err(QueryError::ChallengeRequired);
+                }
+                packet[4..].to_vec()
+            } else {
+                return Err(QueryError::MalformedPacket(
+                    "unknown packet header".to_string(),
+                ));
+            };
+
+            self.options.info_from_payload(&payload)
+        }
+    }
+
+    /// State of a non-blocking A2S_INFO query driven by [`Server::poll_info`].
+    #[derive(Debug, Default)]
+    enum InfoPoll {
+        /// No query in progress.
+        #[default]
+        Idle,
+        /// Request sent, waiting for a challenge or the response.
+        Waiting { challenges: u8, started: Instant },
+        /// Collecting the packets of a split response.
+        Reassembling {
+            response: SplitResponse,
+            started: Instant,
+        },
+    }
+
+    // A2S_INFO Implementation (Non-Blocking)
+    impl Server {
+        /// Drive a non-blocking A2S_INFO query.
+        ///
+        /// The first call sends the request; every call then reads whatever datagrams have
+        /// arrived without blocking, answering a challenge and collecting split packets as
+        /// needed. Returns `Poll::Pending` until the response is complete, so it can be called
+        /// once per frame from a main loop. The query fails with a timed out error once the
+        /// read timeout has elapsed since it started (never, if the read timeout is `None`).
+        ///
+        /// Once `Poll::Ready` is returned, the next call starts a new query. Blocking queries
+        /// should not be made while a polled query is in progress.
+        pub fn poll_info(&mut self) -> Poll<Result<Info, QueryError>> {
+            let result = self.drive_info();
+            if result.is_ready() {
+                self.info_poll = InfoPoll::Idle;
+            }
+            result
+        }
+
+        fn drive_info(&mut self) -> Poll<Result<Info, QueryError>> {
+            self.check_cancelled()?;
+            if let InfoPoll::Idle = self.info_poll {
+                self.send(&request::info(None))?;
+                self.info_poll = InfoPoll::Waiting {
+                    challenges: 0,
+                    started: Instant::now(),
+                };
+            }
+
+            self.transport.set_nonblocking(true)?;
+            let result = self.receive_info();
+            self.transport.set_nonblocking(false)?;
+
+            result
+        }
+
+        fn receive_info(&mut self) -> Poll<Result<Info, QueryError>> {
+            let mut buffer = self.buffer();
+            loop {
+                let bytes_returned = match self.recv(&mut buffer) {
+                    Ok(bytes_returned) => bytes_returned,
+                    // Nothing queued yet on the non-blocking socket
+                    Err(QueryError::Timeout) => {
+                        let started = match &self.info_poll {
+                            InfoPoll::Waiting { started, .. } => *started,
+                            InfoPoll::Reassembling { started, .. } => *started,
+                            InfoPoll::Idle => unreachable!("a query is in progress"),
+                        };
+                        if let Some(timeout) = self.transport.read_timeout()? {
+                            if started.elapsed() >= timeout {
+                                return Poll::Ready(Err(match &self.info_poll {
+                                    InfoPoll::Reassembling { response, .. } => {
+                                        response.incomplete()
+                                    }
+                                    _ => QueryError::Timeout,
+                                }));
+                            }
+                        }
+                        return Poll::Pending;
+                    }
+                    Err(e) => return Poll::Ready(Err(e)),
+                };
+                let packet = &buffer[..bytes_returned];
+                check_packet_len(packet)?;
+
+                let (response, started) = match std::mem::take(&mut self.info_poll) {
+                    InfoPoll::Waiting {
+                        challenges,
+                        started,
+                    } => {
+                        if packet.starts_with(&MULTI_PACKET_RESPONSE_HEADER) {
+                            let response =
+                                SplitResponse::new(packet, self.engine, self.max_payload_bytes)?;
+                            (response, started)
+                        } else if response_kind(packet) == Some(ResponseKind::Challenge) {
+                            // Challenge Received
+                            if challenges == self.max_challenges {
+                                return Poll::Ready(Err(QueryError::TooManyChallenges(
+                                    challenges + 1,
+                                )));
+                            }
+                            let challenge = parse_challenge(packet)?;
+                            let request = request::info(Some(challenge));
+                            self.send(&request)?;
+                            self.info_poll = InfoPoll::Waiting {
+                                challenges: challenges + 1,
+                                started,
+                            };
+                            continue;
+                        } else {
+                            return Poll::Ready(self.options.info_from_payload(&packet[4..]));
+                        }
+                    }
+                    InfoPoll::Reassembling {
+                        mut response,
+                        started,
+                    } => {
+                        response.insert(packet)?;
+                        (response, started)
+                    }
+                    InfoPoll::Idle => unreachable!("a query is in progress"),
+                };
+
+                if response.is_complete() {
+                    let payload = response.into_payload()?;
+                    return Poll::Ready(self.options.info_from_payload(&payload));
+                }
+                self.info_poll = InfoPoll::Reassembling { response, started };
+            }
+        }
+    }
+
+    // A2A_PING Implementation
+    impl Server {
+        /// Measure the round trip time of an A2A_PING request.
+        ///
+        /// A2A_PING is answered by GoldSource servers and some Source builds, and is cheaper
+        /// for the server than A2S_INFO. Servers that do not implement it ignore the request,
+        /// which surfaces as [`QueryError::Timeout`], while any reply other than the ping
+        /// response is an [`QueryError::UnexpectedHeader`].
+        pub fn ping_packet(&self) -> Result<Duration, QueryError> {
+            let request = request::ping();
+            let started = Instant::now();
+            self.send(&request)?;
+
+            let mut buffer = self.buffer();
+            let bytes_returned = self.recv(&mut buffer)?;
+            let elapsed = started.elapsed();
+            let packet = &buffer[..bytes_returned];
+
+            if !packet.starts_with(&SIMPLE_RESPONSE_HEADER)
+                || response_kind(packet) != Some(ResponseKind::Ping)
+            {
+                return Err(QueryError::UnexpectedHeader {
+                    expected: ResponseKind::Ping.as_byte(),
+                    found: packet.get(4).copied().unwrap_or_default(),
+                });
+            }
+
+            Ok(elapsed)
+        }
+
+        /// Measure the reply latency of an A2A_PING request.
+        ///
+        /// **Deprecated by Valve:** modern servers ignore A2A_PING, so this times out against
+        /// them with [`QueryError::Timeout`]. Prefer timing [`Server::info`] for current
+        /// servers.
+        #[deprecated(since = "0.4.10", note = "use `Server::ping_packet`")]
+        pub fn legacy_ping(&self) -> Result<Duration, QueryError> {
+            self.ping_packet()
+        }
+    }
+
+    // A2S_PLAYER Implementation
+    impl Server {
+        /// Query the players on the server.
+        ///
+        /// A server that still honors the `0xFFFFFFFF` challenge replies with the player list
+        /// right away; otherwise its challenge is answered first.
+        pub fn players(&self) -> Result<Vec<Player>, QueryError> {
+            Ok(self.players_with_meta()?.0)
+        }
+
+        /// Query the players like [`Server::players`], along with how the response arrived.
+        pub fn players_with_meta(&self) -> Result<(Vec<Player>, QueryMeta), QueryError> {
+            let (payload, meta) = self.query(
+                |challenge| request::players(challenge.unwrap_or(REQUEST_CHALLENGE)),
+                Some(ResponseKind::Players),
+            )?;
+
+            Ok((self.players_from_payload(&payload)?, meta))
+        }
+
+        /// Query the players, telling a hidden player list apart from an empty server using
+        /// the player count reported by `info`.
+        ///
+        /// CS:GO servers with `host_players_show` set to 0 do not answer A2S_PLAYER at all, and
+        /// with it set to 1 reply with at most a single entry. Either is reported as
+        /// [`QueryError::PlayersHidden`] when `info` reports more players than were listed.
+        ///
+        /// ```no_run
+        /// use valve_server_query::{QueryError, Server};
+        ///
+        /// let server = Server::new("127.0.0.1:27015").expect("Connect to server");
+        /// let info = server.info().expect("Get general server information");
+        /// match server.players_checked(&info) {
+        ///     Ok(players) => println!("{} players online", players.len()),
+        ///     Err(QueryError::PlayersHidden { .. }) => println!("player list hidden by server"),
+        ///     Err(e) => eprintln!("{e}"),
+        /// }
+        /// ```
+        pub fn players_checked(&self, info: &Info) -> Result<Vec<Player>, QueryError> {
+            let reported = *info.player_count();
+            match self.players() {
+                Ok(players) if players.len() <= 1 && reported as usize > players.len() => {
+                    Err(QueryError::PlayersHidden { reported })
+                }
+                Err(QueryError::Timeout) if reported > 0 => {
+                    Err(QueryError::PlayersHidden { reported })
+                }
+                result => result,
+            }
+        }
+
+        fn players_from_payload(&self, payload: &[u8]) -> Result<Vec<Player>, QueryError> {
+            self.options.players_from_payload(payload)
+        }
+
+        /// Display names of the players on the server.
+        ///
+        /// Names are trimmed of surrounding whitespace and control characters are removed.
+        pub fn player_names(&self) -> Result<Vec<String>, QueryError> {
+            let names = self
+                .players()?
+                .iter()
+                .map(|player| {
+                    player
+                        .name()
+                        .chars()
+                        .filter(|c| !c.is_control())
+                        .collect::<String>()
+                        .trim()
+                        .to_string()
+                })
+                .collect();
+
+            Ok(names)
+        }
+    }
+
+    /// A2S_RULES Implementation
+    impl Server {
+        /// Query the rules (console variables) of the server.
+        ///
+        /// A server that still honors the `0xFFFFFFFF` challenge replies with the rules right
+        /// away; otherwise its challenge is answered first.
+        ///
+        /// Rules are looked up by name, so when a server sends several rules of the same name
+        /// only the last is kept in the map; the others are only found in
+        /// [`Rules::duplicates`]. Use [`Server::rules_multi`] to keep every rule in order.
+        pub fn rules(&self) -> Result<Rules, QueryError> {
+            self.rules_from_payload(&self.rules_payload()?)
+        }
+
+        /// Query the rules like [`Server::rules`], along with how the response arrived.
+        pub fn rules_with_meta(&self) -> Result<(Rules, QueryMeta), QueryError> {
+            let (payload, meta) = self.query(
+                |challenge| request::rules(challenge.unwrap_or(REQUEST_CHALLENGE)),
+                Some(ResponseKind::Rules),
+            )?;
+
+            Ok((self.rules_from_payload(&payload)?, meta))
+        }
+
+        /// Query the rules along with the rule count the server declared.
+        ///
+        /// Unlike [`Server::rules`], a count that differs from the number of rules parsed is
+        /// not an error, so the caller can tell a truncated response from a complete one.
+        pub fn rules_with_count(&self) -> Result<(Rules, usize), QueryError> {
+            self.options
+                .rules_with_count_from_payload(&self.rules_payload()?)
+        }
+
+        /// Query the rules of the server as sent, keeping duplicate names and their order.
+        pub fn rules_multi(&self) -> Result<Vec<(String, String)>, QueryError> {
+            self.rule_pairs_from_payload(&self.rules_payload()?)
+        }
+
+        /// Query the rules of a DayZ server, decoding the mods it runs from their binary
+        /// encoding.
+        ///
+        /// [`Server::rules`] would return the binary data as unreadable rules.
+        pub fn rules_dayz(&self) -> Result<DayZRules, QueryError> {
+            DayZRules::from_payload_with(&self.options, &self.rules_payload()?)
+        }
+
+        /// Query the rules, parsing what arrived of a split response that is missing packets.
+        ///
+        /// The rules format degrades gracefully: the rules up to the first missing packet are
+        /// returned, without checking them against the rule count declared by the server.
+        pub fn rules_lenient(&self) -> Result<Rules, QueryError> {
+            self.rules_from_partial_payload(&self.rules_payload_partial(true)?)
+        }
+
+        /// Query the A2S_RULES payload, starting at the header byte.
+        fn rules_payload(&self) -> Result<Vec<u8>, QueryError> {
+            self.rules_payload_partial(false)
+        }
+
+        /// Query the A2S_RULES payload, allowing a partial split response if `partial` is set.
+        fn rules_payload_partial(&self, partial: bool) -> Result<Vec<u8>, QueryError> {
+            Ok(self
+                .query_partial(
+                    |challenge| request::rules(challenge.unwrap_or(REQUEST_CHALLENGE)),
+                    Some(ResponseKind::Rules),
+                    partial,
+                )?
+                .0)
+        }
+
+        /// Parse an A2S_RULES response, see [`ParseOptions::rules_from_payload`].
+        fn rules_from_payload(&self, payload: &[u8]) -> Result<Rules, QueryError> {
+            self.options.rules_from_payload(payload)
+        }
+
+        fn rule_pairs_from_payload(
+            &self,
+            payload: &[u8],
+        ) -> Result<Vec<(String, String)>, QueryError> {
+            self.options.rule_pairs_from_payload(payload)
+        }
+
+        fn rules_from_partial_payload(&self, payload: &[u8]) -> Result<Rules, QueryError> {
+            self.options.rules_from_partial_payload(payload)
+        }
+
+        /// Parse rules from the rule pairs of an A2S_RULES payload.
+        ///
+        /// A rule overwrites an earlier rule of the same name, which is kept in
+        /// [`Rules::duplicates`].
+        #[deprecated(
+            since = "0.4.10",
+            note = "use `protocol::parse_rules` on the response datagram"
+        )]
+        pub fn get_rules(bytes: &[u8]) -> Result<Rules, QueryError> {
+            #[allow(deprecated)]
+            Ok(Self::get_rules_multi(bytes)?.into())
+        }
+
+        /// Parse the rule count of an A2S_RULES payload and that many rules, also returning
+        /// the number of bytes they took.
+        #[deprecated(since = "0.4.10", note = "use `protocol::parse_rules_consumed`")]
+        pub fn parse_rules(bytes: &[u8]) -> Result<(Rules, usize), QueryError> {
+            crate::protocol::parse_rules_consumed(bytes)
+        }
+
+        /// Parse the rule pairs of an A2S_RULES payload, keeping every rule in the order it was
+        /// sent, including rules of the same name.
+        #[deprecated(
+            since = "0.4.10",
+            note = "use `protocol::parse_rule_pairs` on the response datagram"
+        )]
+        pub fn get_rules_multi(bytes: &[u8]) -> Result<Vec<(String, String)>, QueryError> {
+            let options = ParseOptions::default();
+            options.read_rule_pairs(&mut options.reader(bytes), None)
+        }
     }
 
-    #[cfg(test)]
-    mod tests {
+    #[cfg(test)]
+    pub(crate) mod tests {
+
+        use super::*;
+        use std::sync::mpsc;
+        use std::thread;
+
+        /// Spawn a local UDP server that answers each received request with the next set of
+        /// datagrams.
+        fn mock_server(responses: Vec<Vec<Vec<u8>>>) -> SocketAddr {
+            recording_mock_server(responses).0
+        }
+
+        /// Spawn a mock server like [`mock_server`], which also reports each request received.
+        fn recording_mock_server(
+            responses: Vec<Vec<Vec<u8>>>,
+        ) -> (SocketAddr, mpsc::Receiver<Vec<u8>>) {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let addr = socket.local_addr().unwrap();
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                for datagrams in responses {
+                    let (bytes_returned, client) = socket.recv_from(&mut buffer).unwrap();
+                    let _ = sender.send(buffer[..bytes_returned].to_vec());
+                    for datagram in datagrams {
+                        socket.send_to(&datagram, client).unwrap();
+                    }
+                }
+            });
+            (addr, receiver)
+        }
+
+        #[test]
+        fn test_server_unreachable() {
+            // Nothing listens on the port once this socket is dropped
+            let addr = UdpSocket::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            // Only connected sockets are told about ICMP port unreachable replies
+            socket.connect(addr).unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_millis(500)))
+                .unwrap();
+            let server = Server::with_socket(addr, socket);
+
+            assert!(matches!(
+                server.info(),
+                Err(QueryError::ServerUnreachable(_))
+            ));
+            // Still usable afterwards
+            assert!(matches!(
+                server.info(),
+                Err(QueryError::ServerUnreachable(_))
+            ));
+        }
+
+        #[test]
+        fn test_socket_error() {
+            for kind in [
+                io::ErrorKind::ConnectionRefused,
+                io::ErrorKind::ConnectionReset,
+            ] {
+                assert!(matches!(
+                    socket_error(kind.into()),
+                    QueryError::ServerUnreachable(_)
+                ));
+            }
+            assert!(matches!(
+                socket_error(io::ErrorKind::PermissionDenied.into()),
+                QueryError::Io(_)
+            ));
+        }
+
+        /// Build a Source split packet with the given answer ID, packet total and packet number.
+        pub(crate) fn split_packet(id: i32, total: u8, number: u8, payload: &[u8]) -> Vec<u8> {
+            let mut packet = split_packet_pre_orange_box(id, total, number, &[]);
+            packet.extend(1248i16.to_le_bytes());
+            packet.extend(payload);
+            packet
+        }
+
+        /// Build a pre-Orange Box Source split packet, which has no size field.
+        fn split_packet_pre_orange_box(id: i32, total: u8, number: u8, payload: &[u8]) -> Vec<u8> {
+            let mut packet = MULTI_PACKET_RESPONSE_HEADER.to_vec();
+            packet.extend(id.to_le_bytes());
+            packet.extend([total, number]);
+            packet.extend(payload);
+            packet
+        }
+
+        fn info_response() -> Vec<u8> {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([Info::HEADER, 0x11]);
+            response.extend(b"Test Server\x00de_dust2\x00cstrike\x00Counter-Strike\x00");
+            response.extend(10i16.to_le_bytes());
+            response.extend([5, 24, 0, b'd', b'l', 0x00, 0x01]);
+            response.extend(b"1.0.0.0\x00");
+            response
+        }
+
+        #[test]
+        fn test_client_info_raw() {
+            // A plugin blob after the extra data, ending with null bytes
+            let blob = [0x00, 0x2A, 0x07, 0x00, 0x00];
+            let mut response = info_response();
+            response.push(0x80);
+            response.extend(27015i16.to_le_bytes());
+            response.extend(blob);
+            let addr = mock_server(vec![vec![response.clone()]; 2]);
+            let server = Server::new(&addr.to_string()).unwrap();
+
+            let payload = server.info_raw().unwrap();
+            assert_eq!(payload, response[4..]);
+            let info = Info::from_bytes(&payload).unwrap();
+            assert_eq!(info.trailing_bytes(), Some(&blob[..]));
+            assert_eq!(server.info().unwrap(), info);
+        }
+
+        /// Call `poll_info` until it is ready, failing if it takes too long.
+        fn poll_info_until_ready(server: &mut Server) -> Result<Info, QueryError> {
+            for _ in 0..1000 {
+                if let Poll::Ready(result) = server.poll_info() {
+                    return result;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            panic!("poll_info never became ready");
+        }
+
+        /// Build a GoldSource split packet, with the packet number and total in one byte.
+        fn split_packet_goldsource(id: i32, total: u8, number: u8, payload: &[u8]) -> Vec<u8> {
+            let mut packet = MULTI_PACKET_RESPONSE_HEADER.to_vec();
+            packet.extend(id.to_le_bytes());
+            packet.push((number << 4) | total);
+            packet.extend(payload);
+            packet
+        }
+
+        fn players_response() -> Vec<u8> {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x44, 3]);
+            for (index, name, score) in [(0, "Alice", 12i32), (1, "Bob", 3), (2, "Carol", 7)] {
+                response.push(index);
+                response.extend(name.as_bytes());
+                response.push(0);
+                response.extend(score.to_le_bytes());
+                response.extend(60.0f32.to_le_bytes());
+            }
+            response
+        }
+
+        fn expected_rules() -> Rules {
+            [
+                ("sv_cheats", "0"),
+                ("mp_timelimit", "30"),
+                ("hostname", "Test Server"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+        }
+
+        fn rules_response() -> Vec<u8> {
+            let mut payload = SIMPLE_RESPONSE_HEADER.to_vec();
+            payload.push(ResponseKind::Rules.as_byte());
+            payload.extend(3i16.to_le_bytes());
+            payload.extend(b"sv_cheats\x000\x00mp_timelimit\x0030\x00hostname\x00Test Server\x00");
+            payload
+        }
+
+        pub(crate) fn challenge_response() -> Vec<u8> {
+            vec![0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x0A, 0x0B, 0x0C, 0x0D]
+        }
+
+        #[test]
+        fn test_client_init() {
+            let server: Result<_, _> = Server::new("");
+            assert!(
+                server.is_err(),
+                "Server was successfully contructed when it should have failed when parsing URL."
+            );
+        }
+
+        #[test]
+        fn test_client_init_hostname_prefer_v4() {
+            let server = Server::builder("localhost:27015")
+                .address_family_preference(Prefer::V4)
+                .build()
+                .unwrap();
+            assert_eq!(server.addr(), "127.0.0.1:27015".parse().unwrap());
+        }
+
+        #[test]
+        fn test_client_init_no_matching_address() {
+            let server = Server::builder("127.0.0.1:27015")
+                .address_family_preference(Prefer::V6)
+                .build();
+            assert!(matches!(server, Err(QueryError::NoMatchingAddress(_))));
+        }
+
+        #[test]
+        fn test_client_init_default_timeouts() {
+            let server = Server::new("127.0.0.1:27015").unwrap();
+            assert_eq!(
+                server.transport.read_timeout().unwrap(),
+                Some(DEFAULT_TIMEOUT)
+            );
+            assert_eq!(
+                server.transport.write_timeout().unwrap(),
+                Some(DEFAULT_TIMEOUT)
+            );
+        }
+
+        #[test]
+        fn test_client_init_configured_timeouts() {
+            let server = Server::builder("127.0.0.1:27015")
+                .read_timeout(Some(Duration::from_secs(5)))
+                .write_timeout(None)
+                .build()
+                .unwrap();
+            assert_eq!(
+                server.transport.read_timeout().unwrap(),
+                Some(Duration::from_secs(5))
+            );
+            assert_eq!(server.transport.write_timeout().unwrap(), None);
+        }
+
+        #[test]
+        fn test_client_with_socket() {
+            let addr = mock_server(vec![vec![info_response()]]);
+
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(3)))
+                .unwrap();
+
+            // Adopted as-is
+            let server = Server::with_socket(addr, socket);
+            assert_eq!(
+                server.transport.read_timeout().unwrap(),
+                Some(Duration::from_secs(3))
+            );
+            assert_eq!(server.info().unwrap().name(), "Test Server");
+        }
+
+        #[test]
+        #[ignore]
+        fn test_client_init_live() {
+            // Live server I own
+            let server: Result<_, _> = Server::new("54.186.150.6:9879");
+            assert!(
+                server.is_ok(),
+                "Server failed to be contructed when it should have succeeded (LIVE TEST)."
+            );
+        }
+
+        #[test]
+        fn test_client_info() {
+            // Dummy
+            let server = Server::new("127.0.0.1:12345").unwrap();
+            let info: Result<Info, _> = server.info();
+            assert!(
+                info.is_err(),
+                "Target URL is not real, but we got back an Ok response for A2S_INFO."
+            );
+        }
+
+        #[test]
+        fn test_client_info_truncated_response() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.resize(PACKET_SIZE + 100, b'x');
+            let addr = mock_server(vec![vec![response]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let info: Result<Info, _> = server.info();
+            assert!(matches!(
+                info,
+                Err(QueryError::ResponseTruncated {
+                    buffer_size: PACKET_SIZE
+                })
+            ));
+        }
+
+        #[test]
+        fn test_client_info_recv_buffer_size() {
+            let name = "x".repeat(2000);
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x49, 0x11]);
+            for field in [name.as_str(), "de_dust2", "cstrike", "Counter-Strike"] {
+                put_cstring(&mut response, field);
+            }
+            response.extend([0x0A, 0x00, 5, 24, 0, b'd', b'l', 0x00, 0x01]);
+            put_cstring(&mut response, "1.0.0.0");
+            assert!(response.len() > PACKET_SIZE);
+
+            let addr = mock_server(vec![vec![response.clone()], vec![response]]);
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert!(matches!(
+                server.info(),
+                Err(QueryError::ResponseTruncated {
+                    buffer_size: PACKET_SIZE
+                })
+            ));
+
+            let server = Server::builder(&addr.to_string())
+                .recv_buffer_size(4096)
+                .build()
+                .unwrap();
+            assert_eq!(server.info().unwrap().name(), name);
+        }
+
+        #[test]
+        fn test_client_players_multi_packet() {
+            let payload = players_response();
+            let (first, rest) = payload.split_at(12);
+            let (second, third) = rest.split_at(14);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet(5, 3, 1, second),
+                    split_packet(5, 3, 0, first),
+                    split_packet(5, 3, 2, third),
+                ],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let players = server.players().unwrap();
+
+            let names: Vec<&str> = players.iter().map(Player::name).collect();
+            assert_eq!(names, vec!["Alice", "Bob", "Carol"]);
+            let scores: Vec<i32> = players.iter().map(Player::score).collect();
+            assert_eq!(scores, vec![12, 3, 7]);
+        }
+
+        #[test]
+        fn test_client_players_the_ship() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x44, 2]);
+            for (index, name, deaths, money) in [(0, "Alice", 1i32, 900i32), (1, "Bob", 4, 50)] {
+                response.push(index);
+                response.extend(name.as_bytes());
+                response.push(0);
+                response.extend(0i32.to_le_bytes());
+                response.extend(60.0f32.to_le_bytes());
+                response.extend(deaths.to_le_bytes());
+                response.extend(money.to_le_bytes());
+            }
+            let addr = mock_server(vec![vec![response]]);
+
+            let server = Server::builder(&addr.to_string())
+                .the_ship(true)
+                .build()
+                .unwrap();
+            let players = server.players().unwrap();
+
+            assert_eq!(players[1].name(), "Bob");
+            assert_eq!(players[1].deaths(), Some(4));
+            assert_eq!(players[1].money(), Some(50));
+        }
+
+        /// A2S_PLAYER response with a single player named "José" in Windows-1252.
+        fn windows_1252_players_response() -> Vec<u8> {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x44, 1, 0]);
+            response.extend(b"Jos\xE9\x00");
+            response.extend(0i32.to_le_bytes());
+            response.extend(60.0f32.to_le_bytes());
+            response
+        }
+
+        #[test]
+        fn test_client_players_encoding() {
+            let addr = mock_server(vec![
+                vec![windows_1252_players_response()],
+                vec![windows_1252_players_response()],
+            ]);
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.encoding(), Encoding::Utf8Lossy);
+            assert_eq!(server.players().unwrap()[0].name(), "Jos\u{FFFD}");
+
+            server.set_encoding(Encoding::Utf8Strict);
+            assert!(matches!(
+                server.players(),
+                Err(QueryError::InvalidUtf8 { position: 2 })
+            ));
+        }
+
+        #[cfg(feature = "encoding")]
+        #[test]
+        fn test_client_players_windows_1252() {
+            let addr = mock_server(vec![vec![windows_1252_players_response()]]);
+
+            let server = Server::builder(&addr.to_string())
+                .encoding(Encoding::Windows1252)
+                .build()
+                .unwrap();
+            let players = server.players().unwrap();
+            assert_eq!(players[0].name(), "José");
+            assert_eq!(players[0].name_bytes(), b"Jos\xE9");
+        }
+
+        #[test]
+        fn test_client_limits() {
+            let mut negative_count = SIMPLE_RESPONSE_HEADER.to_vec();
+            negative_count.push(ResponseKind::Rules.as_byte());
+            negative_count.extend((-1i16).to_le_bytes());
+            negative_count.extend(b"sv_cheats\x000\x00");
+            let addr = mock_server(vec![
+                vec![rules_response()],
+                vec![negative_count],
+                vec![players_response()],
+                vec![players_response()],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .max_rules(2)
+                .max_players(2)
+                .build()
+                .unwrap();
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::TooManyEntries { limit: 2 })
+            ));
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::TooManyEntries { limit: 2 })
+            ));
+            assert!(matches!(
+                server.players(),
+                Err(QueryError::TooManyEntries { limit: 2 })
+            ));
+
+            let server = Server::builder(&addr.to_string())
+                .max_string_len(4)
+                .build()
+                .unwrap();
+            assert!(matches!(
+                server.players(),
+                Err(QueryError::StringTooLong { limit: 4 })
+            ));
+        }
+
+        #[test]
+        fn test_client_timeout_on_black_hole() {
+            // Bound but never answers
+            let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let server = Server::builder(&silent.local_addr().unwrap().to_string())
+                .read_timeout(Some(Duration::from_millis(50)))
+                .build()
+                .unwrap();
+
+            assert!(matches!(server.info(), Err(QueryError::Timeout)));
+            assert!(matches!(server.get_challenge(), Err(QueryError::Timeout)));
+        }
+
+        #[test]
+        fn test_client_players_hidden() {
+            // info_response() reports 5 players
+            let info = Info::from_bytes(&info_response()[4..]).unwrap();
+
+            // host_players_show 1: only the server entry
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x44, 1, 0]);
+            response.extend(b"Max Players\x00");
+            response.extend(0i32.to_le_bytes());
+            response.extend(0.0f32.to_le_bytes());
+            // host_players_show 0: no reply, then a fallback challenge request with no reply
+            let addr = mock_server(vec![vec![response], vec![], vec![]]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(50)))
+                .build()
+                .unwrap();
+            assert!(matches!(
+                server.players_checked(&info),
+                Err(QueryError::PlayersHidden { reported: 5 })
+            ));
+            assert!(matches!(
+                server.players_checked(&info),
+                Err(QueryError::PlayersHidden { reported: 5 })
+            ));
+        }
+
+        #[test]
+        fn test_client_players_checked_visible() {
+            let info = Info::from_bytes(&info_response()[4..]).unwrap();
+            let addr = mock_server(vec![vec![players_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.players_checked(&info).unwrap().len(), 3);
+        }
+
+        #[test]
+        fn test_client_ping_packet() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.push(0x6A);
+            response.extend(b"00000000000000\x00");
+            let addr = mock_server(vec![vec![response], vec![info_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert!(server.ping_packet().unwrap() < Duration::from_secs(1));
+            assert!(matches!(
+                server.ping_packet(),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x6A,
+                    found: 0x49
+                })
+            ));
+        }
+
+        #[test]
+        fn test_client_ping_packet_unanswered() {
+            let addr = mock_server(vec![vec![]]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(50)))
+                .build()
+                .unwrap();
+            assert!(matches!(server.ping_packet(), Err(QueryError::Timeout)));
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_client_legacy_ping() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.push(0x6A);
+            let addr = mock_server(vec![vec![response], vec![]]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(50)))
+                .build()
+                .unwrap();
+            assert!(server.legacy_ping().is_ok());
+            assert!(matches!(server.legacy_ping(), Err(QueryError::Timeout)));
+        }
+
+        #[test]
+        fn test_client_rejects_oversized_split_response() {
+            // Claims 255 packets, each carrying 1000 bytes of junk
+            let junk = [0x20; 1000];
+            let addr = mock_server(vec![(0..3)
+                .map(|i| split_packet(9, 255, i, &junk))
+                .collect()]);
+
+            let server = Server::builder(&addr.to_string())
+                .max_payload_bytes(2500)
+                .build()
+                .unwrap();
+            assert!(matches!(
+                server.info(),
+                Err(QueryError::PayloadTooLarge { limit: 2500 })
+            ));
+        }
+
+        #[test]
+        fn test_client_wrong_response_type() {
+            let addr = mock_server(vec![
+                vec![players_response()],
+                vec![challenge_response()],
+                vec![info_response()],
+                vec![challenge_response()],
+                vec![players_response()],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert!(matches!(
+                server.info(),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x49,
+                    found: 0x44
+                })
+            ));
+            assert!(matches!(
+                server.players(),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x44,
+                    found: 0x49
+                })
+            ));
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x45,
+                    found: 0x44
+                })
+            ));
+        }
+
+        #[test]
+        fn test_client_players_and_rules_without_challenge() {
+            let (addr, requests) =
+                recording_mock_server(vec![vec![players_response()], vec![rules_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(
+                requests,
+                vec![
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF],
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0xFF, 0xFF, 0xFF, 0xFF],
+                ]
+            );
+        }
+
+        #[test]
+        fn test_client_players_and_rules_with_challenge() {
+            let (addr, requests) = recording_mock_server(vec![
+                vec![challenge_response()],
+                vec![players_response()],
+                vec![challenge_response()],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .challenge_lifetime(Duration::ZERO)
+                .build()
+                .unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(
+                requests,
+                vec![
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0xFF, 0xFF, 0xFF, 0xFF],
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x55, 0x0A, 0x0B, 0x0C, 0x0D],
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0xFF, 0xFF, 0xFF, 0xFF],
+                    vec![0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0x0A, 0x0B, 0x0C, 0x0D],
+                ]
+            );
+        }
+
+        #[test]
+        fn test_client_drains_stale_reply() {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let addr = socket.local_addr().unwrap();
+            let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+            client
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+
+            // Late reply to an earlier A2S_INFO query, queued before the next query is sent
+            socket
+                .send_to(&info_response(), client.local_addr().unwrap())
+                .unwrap();
+            thread::sleep(Duration::from_millis(50));
+
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                let (_, client) = socket.recv_from(&mut buffer).unwrap();
+                socket.send_to(&rules_response(), client).unwrap();
+            });
+
+            let server = Server::with_socket(addr, client);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+        }
+
+        #[test]
+        fn test_client_info_no_challenge() {
+            let (addr, requests) =
+                recording_mock_server(vec![vec![info_response()], vec![challenge_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.info_no_challenge().unwrap().name(), "Test Server");
+            assert!(matches!(
+                server.info_no_challenge(),
+                Err(QueryError::ChallengeRequired)
+            ));
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(requests, vec![request::info(None); 2]);
+        }
+
+        #[test]
+        fn test_client_get_challenge() {
+            let addr = mock_server(vec![vec![challenge_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.get_challenge().unwrap(), [0x0A, 0x0B, 0x0C, 0x0D]);
+        }
+
+        #[test]
+        fn test_client_challenge_with_zero_bytes() {
+            let challenge = vec![0xFF, 0xFF, 0xFF, 0xFF, 0x41, 0x00, 0x0B, 0x00, 0x00, 0x00];
+            let (addr, requests) = recording_mock_server(vec![
+                vec![challenge.clone()],
+                vec![players_response()],
+                vec![challenge],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .challenge_lifetime(Duration::ZERO)
+                .build()
+                .unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(requests[1], request::players([0x00, 0x0B, 0x00, 0x00]));
+            assert_eq!(requests[3], request::rules([0x00, 0x0B, 0x00, 0x00]));
+        }
+
+        #[test]
+        fn test_client_challenge() {
+            let addr = mock_server(vec![vec![challenge_response()]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.challenge().unwrap(), 0x0D0C_0B0A);
+        }
+
+        #[test]
+        fn test_client_legacy_challenge_after_timeout() {
+            // Ignores the player request with 0xFFFFFFFF, only answering A2S_SERVERQUERY_GETCHALLENGE
+            let addr = mock_server(vec![
+                vec![],
+                vec![challenge_response()],
+                vec![players_response()],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(100)))
+                .build()
+                .unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+        }
 
-        use super::*;
+        #[test]
+        fn test_client_legacy_challenge_after_unexpected_header() {
+            let addr = mock_server(vec![
+                vec![vec![0xFF, 0xFF, 0xFF, 0xFF, 0x6C]],
+                vec![challenge_response()],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.rules().unwrap(), expected_rules());
+        }
 
         #[test]
-        fn test_client_init() {
-            let server: Result<_, _> = Server::new("");
-            if let Err(_) = server {
-            } else {
-                assert!(false, "Server was successfully contructed when it should have failed when parsing URL.")
-            }
+        fn test_client_rechallenge() {
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![challenge_response()],
+                vec![info_response()],
+                vec![challenge_response()],
+                vec![challenge_response()],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.info().unwrap().name(), "Test Server");
+            assert_eq!(server.rules().unwrap(), expected_rules());
         }
 
         #[test]
-        #[ignore]
-        fn test_client_init_live() {
-            // Live server I own
-            let server: Result<_, _> = Server::new("54.186.150.6:9879");
-            if let Ok(_) = server {
-            } else {
-                assert!(
-                    false,
-                    "Server failed to be contructed when it should have succeeded (LIVE TEST)."
-                )
+        fn test_server_from_str() {
+            let addr = mock_server(vec![vec![info_response()]]);
+
+            let server: Server = addr.to_string().parse().unwrap();
+            assert_eq!(server.info().unwrap().name(), "Test Server");
+
+            assert!(matches!(
+                "localhost".parse::<Server>(),
+                Err(QueryError::Io(_))
+            ));
+        }
+
+        #[test]
+        fn test_client_reuses_fresh_challenge() {
+            let (addr, requests) = recording_mock_server(vec![
+                vec![challenge_response()],
+                vec![players_response()],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(requests.len(), 3);
+            assert_eq!(
+                requests[2],
+                vec![0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0x0A, 0x0B, 0x0C, 0x0D]
+            );
+        }
+
+        #[test]
+        fn test_client_expired_challenge_is_not_reused() {
+            let (addr, requests) = recording_mock_server(vec![
+                vec![challenge_response()],
+                vec![players_response()],
+                vec![rules_response()],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .challenge_lifetime(Duration::ZERO)
+                .build()
+                .unwrap();
+            assert_eq!(server.players().unwrap().len(), 3);
+            assert_eq!(server.rules().unwrap(), expected_rules());
+
+            let requests: Vec<Vec<u8>> = requests.iter().collect();
+            assert_eq!(
+                requests[2],
+                vec![0xFF, 0xFF, 0xFF, 0xFF, 0x56, 0xFF, 0xFF, 0xFF, 0xFF]
+            );
+        }
+
+        #[test]
+        fn test_client_too_many_challenges() {
+            let addr = mock_server(vec![vec![challenge_response()]; 3]);
+
+            let server = Server::builder(&addr.to_string())
+                .max_challenges(2)
+                .build()
+                .unwrap();
+            assert!(matches!(
+                server.players(),
+                Err(QueryError::TooManyChallenges(3))
+            ));
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet() {
+            let payload = rules_response();
+            let (first, second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![split_packet(7, 2, 1, second), split_packet(7, 2, 0, first)],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let rules = server.rules().unwrap();
+            assert_eq!(rules, expected_rules());
+        }
+
+        #[test]
+        fn test_client_rules_many_packets_with_duplicates() {
+            let mut sent: Vec<(String, String)> = (0..200)
+                .map(|i| (format!("modlist_{i}"), format!("workshop_mod_{i:08}")))
+                .collect();
+            sent.insert(50, ("sv_tags".to_string(), "pve".to_string()));
+            sent.push(("sv_tags".to_string(), "pvp,modded".to_string()));
+
+            let mut payload = SIMPLE_RESPONSE_HEADER.to_vec();
+            payload.push(0x45);
+            payload.extend((sent.len() as i16).to_le_bytes());
+            for (name, value) in &sent {
+                payload.extend(name.as_bytes());
+                payload.push(0);
+                payload.extend(value.as_bytes());
+                payload.push(0);
             }
+            let chunk_size = payload.len().div_ceil(8);
+            let mut packets: Vec<Vec<u8>> = payload
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(number, chunk)| split_packet(3, 8, number as u8, chunk))
+                .collect();
+            assert_eq!(packets.len(), 8);
+            packets.reverse();
+
+            let addr = mock_server(vec![packets]);
+            let server = Server::new(&addr.to_string()).unwrap();
+            let rules = server.rules().unwrap();
+
+            assert_eq!(rules.len(), 201);
+            assert_eq!(rules["modlist_199"], "workshop_mod_00000199");
+            assert_eq!(rules["sv_tags"], "pvp,modded");
+            assert_eq!(
+                rules.duplicates(),
+                &[("sv_tags".to_string(), "pve".to_string())]
+            );
         }
 
         #[test]
-        fn test_client_info() {
-            // Dummy
-            let server = Server::new("127.0.0.1:12345").unwrap();
-            let info: Result<Info, _> = server.info();
-            if let Err(_) = info {
-            } else {
-                assert!(
-                    false,
-                    "Target URL is not real, but we got back an Ok response for A2S_INFO."
-                )
+        fn test_client_rules_multi_keeps_duplicates() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.push(ResponseKind::Rules.as_byte());
+            response.extend(3i16.to_le_bytes());
+            response.extend(b"sv_tags\x00pve\x00mp_timelimit\x0030\x00sv_tags\x00pvp\x00");
+            let addr = mock_server(vec![vec![response.clone()]]);
+
+            let expected = vec![
+                ("sv_tags".to_string(), "pve".to_string()),
+                ("mp_timelimit".to_string(), "30".to_string()),
+                ("sv_tags".to_string(), "pvp".to_string()),
+            ];
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.rules_multi().unwrap(), expected);
+            assert_eq!(
+                crate::protocol::parse_rule_pairs(&response).unwrap(),
+                expected
+            );
+
+            let rules = Rules::from(expected);
+            assert_eq!(rules.len(), 2);
+            assert_eq!(rules["sv_tags"], "pvp");
+            assert_eq!(
+                rules.duplicates(),
+                &[("sv_tags".to_string(), "pve".to_string())]
+            );
+        }
+
+        #[test]
+        fn test_client_compressed_split_response() {
+            let payload = rules_response();
+            let (first, second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet(i32::MIN | 7, 2, 0, first),
+                    split_packet(i32::MIN | 7, 2, 1, second),
+                ],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::CompressedResponse)
+            ));
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_pre_orange_box() {
+            let payload = rules_response();
+            let (first, second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet_pre_orange_box(7, 2, 0, first),
+                    split_packet_pre_orange_box(7, 2, 1, second),
+                ],
+            ]);
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            server.set_engine(Engine::SourcePreOrangeBox);
+            let rules = server.rules().unwrap();
+            assert_eq!(rules, expected_rules());
+        }
+
+        #[test]
+        fn test_split_response_meta() {
+            let mut response =
+                SplitResponse::new(&split_packet(7, 3, 0, b"abc"), Engine::Source, 1024).unwrap();
+            response.insert(&split_packet(7, 3, 2, b"abcdef")).unwrap();
+            let meta = response.meta();
+            assert_eq!(meta.fragments, 2);
+            assert!(!meta.compressed);
+            assert_eq!(meta.largest_packet, 12 + 6);
+
+            // The high bit of the answer ID flags a compressed response
+            let compressed = split_packet(i32::MIN | 7, 1, 0, b"abc");
+            let response = SplitResponse::new(&compressed, Engine::Source, 1024).unwrap();
+            assert!(response.meta().compressed);
+            let response = SplitResponse::new(
+                &split_packet_goldsource(i32::MIN | 7, 1, 0, b"abc"),
+                Engine::GoldSource,
+                1024,
+            )
+            .unwrap();
+            assert!(!response.meta().compressed);
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_goldsource() {
+            let payload = rules_response();
+            let (first, rest) = payload.split_at(15);
+            let (second, third) = rest.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet_goldsource(9, 3, 2, third),
+                    split_packet_goldsource(9, 3, 0, first),
+                    split_packet_goldsource(9, 3, 1, second),
+                ],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .engine(Engine::GoldSource)
+                .build()
+                .unwrap();
+            let rules = server.rules().unwrap();
+            assert_eq!(rules, expected_rules());
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_ignores_other_answer_ids() {
+            let payload = rules_response();
+            let (first, second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet(7, 2, 0, first),
+                    split_packet(8, 2, 1, b"stale\x00garbage\x00"),
+                    split_packet(8, 2, 0, b"more garbage"),
+                    split_packet(7, 2, 1, second),
+                ],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let rules = server.rules().unwrap();
+            assert_eq!(rules, expected_rules());
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_incomplete() {
+            let payload = rules_response();
+            let (first, _second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet(7, 2, 0, first),
+                    split_packet(8, 2, 1, b"stale\x00garbage\x00"),
+                ],
+            ]);
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            server
+                .set_read_timeout(Some(Duration::from_millis(100)))
+                .unwrap();
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::IncompleteResponse {
+                    received: 1,
+                    expected: 2,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn test_client_cancel_flag() {
+            let payload = rules_response();
+            let (first, _rest) = payload.split_at(20);
+            let addr = mock_server(vec![vec![split_packet(7, 2, 0, first)]]);
+
+            let cancel = Arc::new(AtomicBool::new(false));
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_secs(5)))
+                .cancel_flag(cancel.clone())
+                .build()
+                .unwrap();
+            let canceller = {
+                let cancel = cancel.clone();
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(200));
+                    cancel.store(true, Ordering::Relaxed);
+                })
+            };
+
+            // Waiting on the lost second packet
+            let started = Instant::now();
+            assert!(matches!(server.rules(), Err(QueryError::Cancelled)));
+            assert!(started.elapsed() < Duration::from_secs(2));
+            assert_eq!(
+                server.transport.read_timeout().unwrap(),
+                Some(Duration::from_secs(5))
+            );
+            canceller.join().unwrap();
+
+            // Stays cancelled while the flag is set
+            assert!(matches!(server.info(), Err(QueryError::Cancelled)));
+        }
+
+        #[test]
+        fn test_client_cancel_flag_unset() {
+            let addr = mock_server(vec![vec![info_response()]]);
+            let server = Server::builder(&addr.to_string())
+                .cancel_flag(Arc::new(AtomicBool::new(false)))
+                .build()
+                .unwrap();
+            assert_eq!(server.info().unwrap().name(), "Test Server");
+
+            // Times out as usual
+            let started = Instant::now();
+            assert!(matches!(server.info(), Err(QueryError::Timeout)));
+            assert!(started.elapsed() >= Duration::from_millis(900));
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_reassembly_deadline() {
+            let payload = rules_response();
+            let (first, rest) = payload.split_at(20);
+            let (second, _third) = rest.split_at(20);
+
+            // Keeps the split response trickling in, but never sends the last packet
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let addr = socket.local_addr().unwrap();
+            let packets = [split_packet(7, 3, 0, first), split_packet(7, 3, 1, second)];
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                let (_, client) = socket.recv_from(&mut buffer).unwrap();
+                for packet in packets.iter().cycle().take(50) {
+                    let _ = socket.send_to(packet, client);
+                    thread::sleep(Duration::from_millis(20));
+                }
+            });
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(500)))
+                .reassembly_timeout(Some(Duration::from_millis(100)))
+                .build()
+                .unwrap();
+            let started = Instant::now();
+            assert!(matches!(
+                server.rules(),
+                Err(QueryError::IncompleteResponse {
+                    received: 2,
+                    expected: 3,
+                    ..
+                })
+            ));
+            assert!(started.elapsed() < Duration::from_millis(500));
+            assert_eq!(
+                server.transport.read_timeout().unwrap(),
+                Some(Duration::from_millis(500))
+            );
+        }
+
+        #[test]
+        fn test_client_rules_split_total_mismatch() {
+            let payload = rules_response();
+            let (first, rest) = payload.split_at(20);
+            let (second, _third) = rest.split_at(20);
+
+            // Declares 3 packets, but sends 2 and repeats the second
+            let packets = vec![
+                split_packet(7, 3, 0, first),
+                split_packet(7, 3, 1, second),
+                split_packet(7, 3, 1, second),
+            ];
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                packets.clone(),
+                vec![challenge_response()],
+                packets,
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(100)))
+                .challenge_lifetime(Duration::ZERO)
+                .build()
+                .unwrap();
+            let Err(QueryError::IncompleteResponse {
+                received,
+                expected,
+                missing,
+                assembled,
+            }) = server.rules()
+            else {
+                panic!("split response should be incomplete");
+            };
+            assert_eq!((received, expected, missing), (2, 3, vec![2]));
+            assert_eq!(assembled, 40);
+
+            // The rules cut off in the missing packet are dropped
+            let rules = server.rules_lenient().unwrap();
+            assert_eq!(rules.len(), 2);
+            assert_eq!(rules["sv_cheats"], "0");
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_skips_interleaved_datagrams() {
+            let payload = rules_response();
+            let (first, second) = payload.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![
+                    split_packet(7, 2, 0, first),
+                    info_response(),
+                    vec![0xFF, 0xFF, 0xFF, 0xFE, 0x07],
+                    split_packet(7, 2, 1, second),
+                ],
+            ]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            assert_eq!(server.rules().unwrap(), expected_rules());
+        }
+
+        #[test]
+        fn test_client_rules_multi_packet_reports_missing_packets() {
+            let payload = rules_response();
+            let (first, rest) = payload.split_at(20);
+            let (_second, third) = rest.split_at(20);
+
+            let addr = mock_server(vec![
+                vec![challenge_response()],
+                vec![split_packet(7, 3, 0, first), split_packet(7, 3, 2, third)],
+            ]);
+
+            let server = Server::builder(&addr.to_string())
+                .read_timeout(Some(Duration::from_millis(100)))
+                .build()
+                .unwrap();
+            let Err(QueryError::IncompleteResponse {
+                received,
+                expected,
+                missing,
+                ..
+            }) = server.rules()
+            else {
+                panic!("split response should be incomplete");
+            };
+            assert_eq!((received, expected, missing), (2, 3, vec![1]));
+        }
+
+        /// Spawn a local UDP server that answers a single request from a different port.
+        fn mock_server_replying_from_other_port(response: Vec<u8>) -> SocketAddr {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let other = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let addr = socket.local_addr().unwrap();
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                let (_, client) = socket.recv_from(&mut buffer).unwrap();
+                other.send_to(&response, client).unwrap();
+            });
+            addr
+        }
+
+        #[test]
+        fn test_client_info_ignores_other_sources() {
+            let addr = mock_server_replying_from_other_port(info_response());
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            server
+                .set_read_timeout(Some(Duration::from_millis(100)))
+                .unwrap();
+            assert!(matches!(server.info(), Err(QueryError::Timeout)));
+        }
+
+        #[test]
+        fn test_client_info_allow_source_port_mismatch() {
+            let addr = mock_server_replying_from_other_port(info_response());
+
+            let server = Server::builder(&addr.to_string())
+                .allow_source_port_mismatch(true)
+                .build()
+                .unwrap();
+            assert_eq!(server.info().unwrap().name(), "Test Server");
+        }
+
+        #[test]
+        fn test_rules_from_payload_utf8() {
+            let mut payload = vec![ResponseKind::Rules.as_byte()];
+            payload.extend(1i16.to_le_bytes());
+            payload.extend("名前\0Привет\0".as_bytes());
+
+            let server = Server::new("127.0.0.1:27015").unwrap();
+            let rules = server.rules_from_payload(&payload).unwrap();
+            assert_eq!(rules.get("名前").map(String::as_str), Some("Привет"));
+        }
+
+        #[test]
+        fn test_rules_from_payload_count_mismatch() {
+            let mut payload = vec![ResponseKind::Rules.as_byte()];
+            payload.extend(2i16.to_le_bytes());
+            payload.extend(b"sv_cheats\x000\x00");
+
+            assert!(matches!(
+                Server::new("127.0.0.1:27015")
+                    .unwrap()
+                    .rules_from_payload(&payload),
+                Err(QueryError::CountMismatch {
+                    expected: 2,
+                    found: 1
+                })
+            ));
+        }
+
+        #[test]
+        fn test_client_rules_with_count() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.push(ResponseKind::Rules.as_byte());
+            response.extend(3i16.to_le_bytes());
+            response.extend(b"sv_cheats\x000\x00mp_timelimit\x0030\x00");
+            let mut complete = response.clone();
+            complete[5] = 2;
+            let addr = mock_server(vec![vec![response], vec![complete]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let (rules, count) = server.rules_with_count().unwrap();
+            assert_eq!(count, 3);
+            assert_eq!(rules.len(), 2);
+            assert_eq!(rules["mp_timelimit"], "30");
+
+            let (rules, count) = server.rules_with_count().unwrap();
+            assert_eq!(count, rules.len());
+        }
+
+        #[test]
+        fn test_rules_to_bytes_round_trip() {
+            // Xorshift, for reproducible random rules
+            let mut state: u32 = 0x2545_F491;
+            let mut random = |bound: u32| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state % bound
+            };
+            let alphabet: Vec<char> = "abcxyz_019 .,;\"'ßéПр名前🎮".chars().collect();
+
+            let server = Server::new("127.0.0.1:27015").unwrap();
+            for _ in 0..200 {
+                let mut map = HashMap::new();
+                for _ in 0..random(40) {
+                    let [name, value] = [(); 2].map(|_| {
+                        (0..random(25))
+                            .map(|_| alphabet[random(alphabet.len() as u32) as usize])
+                            .collect::<String>()
+                    });
+                    map.insert(name, value);
+                }
+                let rules = Rules::from(map);
+
+                let payload = rules_to_bytes(&rules);
+                assert_eq!(payload[0], ResponseKind::Rules.as_byte());
+                assert_eq!(
+                    i16::from_le_bytes([payload[1], payload[2]]) as usize,
+                    rules.len()
+                );
+                assert_eq!(
+                    crate::protocol::parse_rules_consumed(&payload[1..]).unwrap(),
+                    (rules.clone(), payload.len() - 1)
+                );
+                assert_eq!(server.rules_from_payload(&payload).unwrap(), rules);
             }
+
+            // Duplicates are written before the rules overwriting them
+            let rules = Rules::from(vec![
+                ("sv_tags".to_string(), "a".to_string()),
+                ("sv_tags".to_string(), "b".to_string()),
+                ("sv_cheats".to_string(), "0".to_string()),
+            ]);
+            let payload = rules_to_bytes(&rules);
+            assert_eq!(payload[1], 3);
+            assert_eq!(server.rules_from_payload(&payload).unwrap(), rules);
+        }
+
+        #[test]
+        fn test_client_player_names() {
+            let mut response = SIMPLE_RESPONSE_HEADER.to_vec();
+            response.extend([0x44, 2]);
+            response.push(0);
+            response.extend(b"  Alice\t\x00");
+            response.extend(12i32.to_le_bytes());
+            response.extend(61.5f32.to_le_bytes());
+            response.push(1);
+            response.extend(b"Bob\x00");
+            response.extend(3i32.to_le_bytes());
+            response.extend(5.0f32.to_le_bytes());
+
+            let addr = mock_server(vec![vec![challenge_response()], vec![response]]);
+
+            let server = Server::new(&addr.to_string()).unwrap();
+            let names = server.player_names().unwrap();
+            assert_eq!(names, vec!["Alice".to_string(), "Bob".to_string()]);
+        }
+
+        #[test]
+        fn test_rules_typed_getters() {
+            let rules: Rules = [
+                ("sv_cheats", "0"),
+                ("sv_alltalk", "1"),
+                ("mp_friendlyfire", "True"),
+                ("mp_timelimit", " 30 "),
+                ("sv_gravity", "800.5"),
+                ("hostname", "Test Server"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+            assert_eq!(rules.get_bool("sv_cheats"), Some(false));
+            assert_eq!(rules.get_bool("sv_alltalk"), Some(true));
+            assert_eq!(rules.get_bool("mp_friendlyfire"), Some(true));
+            assert_eq!(rules.get_bool("hostname"), None);
+            assert_eq!(rules.get_i64("mp_timelimit"), Some(30));
+            assert_eq!(rules.get_i64("sv_gravity"), None);
+            assert_eq!(rules.get_f64("sv_gravity"), Some(800.5));
+            assert_eq!(rules.get_f64("missing"), None);
+
+            // Still usable as a map
+            assert_eq!(rules.len(), 6);
+            assert_eq!(
+                rules.get("hostname").map(String::as_str),
+                Some("Test Server")
+            );
+        }
+
+        #[test]
+        fn test_rules_diff() {
+            let rules = |pairs: &[(&str, &str)]| -> Rules {
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            };
+            let before = rules(&[("sv_cheats", "0"), ("mp_timelimit", "20"), ("sv_tags", "")]);
+            let after = rules(&[
+                ("sv_cheats", "1"),
+                ("mp_timelimit", "20"),
+                ("sv_gravity", "800"),
+            ]);
+
+            let diff = before.diff(&after);
+            assert_eq!(
+                diff.added,
+                BTreeMap::from([("sv_gravity".to_string(), "800".to_string())])
+            );
+            assert_eq!(
+                diff.removed,
+                BTreeMap::from([("sv_tags".to_string(), String::new())])
+            );
+            assert_eq!(
+                diff.changed,
+                BTreeMap::from([("sv_cheats".to_string(), ("0".to_string(), "1".to_string()))])
+            );
+            assert!(!diff.is_empty());
+
+            let reverse = after.diff(&before);
+            assert_eq!(reverse.added, diff.removed);
+            assert_eq!(reverse.removed, diff.added);
+            assert!(before.diff(&before).is_empty());
+        }
+
+        #[test]
+        fn test_client_poll_info_with_challenge() {
+            let addr = mock_server(vec![vec![challenge_response()], vec![info_response()]]);
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            let info = poll_info_until_ready(&mut server).unwrap();
+            assert_eq!(info.name(), "Test Server");
+            assert_eq!(info.map(), "de_dust2");
+        }
+
+        #[test]
+        fn test_client_poll_info_multi_packet() {
+            let payload = info_response();
+            let (first, second) = payload.split_at(30);
+            let addr = mock_server(vec![vec![
+                split_packet(3, 2, 0, first),
+                split_packet(3, 2, 1, second),
+            ]]);
+
+            let mut server = Server::new(&addr.to_string()).unwrap();
+            let info = poll_info_until_ready(&mut server).unwrap();
+            assert_eq!(info.name(), "Test Server");
+            assert_eq!(info.game_version(), "1.0.0.0");
+        }
+
+        #[test]
+        fn test_client_poll_info_pending_then_timeout() {
+            // Bound but never answers
+            let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let mut server = Server::new(&silent.local_addr().unwrap().to_string()).unwrap();
+            server
+                .set_read_timeout(Some(Duration::from_millis(50)))
+                .unwrap();
+
+            assert!(server.poll_info().is_pending());
+            thread::sleep(Duration::from_millis(60));
+            assert!(matches!(
+                server.poll_info(),
+                Poll::Ready(Err(QueryError::Timeout))
+            ));
         }
 
         #[test]
@@ -1009,13 +9378,10 @@ pub mod server {
             // Live server I own
             let server = Server::new("54.186.150.6:9879").unwrap();
             let info: Result<Info, _> = server.info();
-            if let Ok(_) = info {
-            } else {
-                assert!(
-                    false,
-                    "Target URL is real and live, but we got back an Err response for A2S_INFO."
-                )
-            }
+            assert!(
+                info.is_ok(),
+                "Target URL is real and live, but we got back an Err response for A2S_INFO."
+            );
         }
         #[test]
         #[ignore]
@@ -1023,13 +9389,10 @@ pub mod server {
             // Live server I own
             let server = Server::new("54.186.150.6:9879").unwrap();
             let players: Result<Vec<Player>, _> = server.players();
-            if let Ok(_) = players {
-            } else {
-                assert!(
-                    false,
-                    "Target URL is real and live, but we got back an Err response for A2S_PLAYER."
-                )
-            }
+            assert!(
+                players.is_ok(),
+                "Target URL is real and live, but we got back an Err response for A2S_PLAYER."
+            );
         }
         #[test]
         #[ignore]
@@ -1037,35 +9400,624 @@ pub mod server {
             // Live server I own
             let server = Server::new("54.186.150.6:9879").unwrap();
             let rules: Result<Rules, _> = server.rules();
-            if let Ok(_) = rules {
+            assert!(
+                rules.is_ok(),
+                "Target URL is real and live, but we got back an Err response for A2S_RULES."
+            );
+        }
+    }
+}
+
+/// Discover servers by querying many addresses at once.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use valve_server_query::discovery;
+///
+/// let hosts = discovery::hosts("192.168.1.0/24").expect("Valid CIDR range");
+/// let found = discovery::scan(hosts, &[27015, 27016], Duration::from_secs(2))
+///     .expect("Scan local network");
+/// for (addr, info) in found {
+///     println!("{addr}: {}", info.name());
+/// }
+/// ```
+pub mod discovery {
+
+    use std::collections::HashMap;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+    use std::panic;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::error::QueryError;
+    use crate::models::info::Info;
+    use crate::protocol::parse_challenge;
+    use crate::request;
+    use crate::{PACKET_SIZE, SIMPLE_RESPONSE_HEADER};
+
+    /// Shortest prefix [`hosts`] accepts, a range of 65,536 addresses.
+    pub const MIN_PREFIX: u32 = 16;
+
+    /// Host addresses of an IPv4 CIDR range, such as `192.168.1.0/24`.
+    ///
+    /// The network and broadcast addresses are left out of ranges larger than two addresses.
+    /// Ranges with a prefix shorter than [`MIN_PREFIX`] fail with
+    /// [`QueryError::InvalidAddress`], rather than listing millions of hosts.
+    pub fn hosts(cidr: &str) -> Result<Vec<IpAddr>, QueryError> {
+        let invalid = || QueryError::InvalidAddress(cidr.to_string());
+
+        let (network, prefix) = cidr.split_once('/').ok_or_else(invalid)?;
+        let network: Ipv4Addr = network.parse().map_err(|_| invalid())?;
+        let prefix: u32 = prefix.parse().map_err(|_| invalid())?;
+        if !(MIN_PREFIX..=32).contains(&prefix) {
+            return Err(invalid());
+        }
+
+        let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+        let first = u32::from(network) & mask;
+        let last = first | !mask;
+        let (first, last) = if prefix < 31 {
+            (first + 1, last - 1)
+        } else {
+            (first, last)
+        };
+
+        Ok((first..=last)
+            .map(|host| IpAddr::V4(Ipv4Addr::from(host)))
+            .collect())
+    }
+
+    /// Send A2S_INFO to every port of every host and collect the servers that answer within
+    /// `timeout`.
+    ///
+    /// All requests go out at once from a single socket per address family, and challenges
+    /// are answered as they arrive, so scanning a /24 takes about `timeout` regardless of its
+    /// size. Hosts that do not answer, or answer with anything but a single-packet A2S_INFO
+    /// response, are skipped.
+    pub fn scan<I>(
+        hosts: I,
+        ports: &[u16],
+        timeout: Duration,
+    ) -> Result<Vec<(SocketAddr, Info)>, QueryError>
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        let (v4, v6): (Vec<SocketAddr>, Vec<SocketAddr>) = hosts
+            .into_iter()
+            .flat_map(|host| ports.iter().map(move |port| SocketAddr::new(host, *port)))
+            .partition(SocketAddr::is_ipv4);
+        let deadline = Instant::now() + timeout;
+
+        thread::scope(|scope| {
+            let v4 = scope.spawn(|| scan_family(IpAddr::V4(Ipv4Addr::UNSPECIFIED), &v4, deadline));
+            let v6 = scope.spawn(|| scan_family(IpAddr::V6(Ipv6Addr::UNSPECIFIED), &v6, deadline));
+
+            let mut found = v4
+                .join()
+                .unwrap_or_else(|panic| panic::resume_unwind(panic))?;
+            found.extend(
+                v6.join()
+                    .unwrap_or_else(|panic| panic::resume_unwind(panic))?,
+            );
+            Ok(found)
+        })
+    }
+
+    /// Scan targets of a single address family from a socket bound to `unspecified`.
+    fn scan_family(
+        unspecified: IpAddr,
+        targets: &[SocketAddr],
+        deadline: Instant,
+    ) -> Result<Vec<(SocketAddr, Info)>, QueryError> {
+        if targets.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let socket = UdpSocket::bind((unspecified, 0))?;
+        for target in targets {
+            // Unreachable hosts are skipped, like hosts that never answer
+            let _ = socket.send_to(&request::info(None), target);
+        }
+
+        let mut found: HashMap<SocketAddr, Info> = HashMap::new();
+        let mut buffer = [0; PACKET_SIZE];
+        while found.len() < targets.len() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            socket.set_read_timeout(Some(remaining))?;
+
+            let (bytes_returned, source) = match socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(_) => continue,
+            };
+            let packet = &buffer[..bytes_returned];
+            if !targets.contains(&source)
+                || found.contains_key(&source)
+                || !packet.starts_with(&SIMPLE_RESPONSE_HEADER)
+            {
+                continue;
+            }
+
+            if let Ok(challenge) = parse_challenge(packet) {
+                let _ = socket.send_to(&request::info(Some(challenge)), source);
+            } else if let Ok(info) = Info::from_bytes(&packet[4..]) {
+                found.insert(source, info);
             } else {
-                assert!(
-                    false,
-                    "Target URL is real and live, but we got back an Err response for A2S_RULES."
-                )
+                debug!("skipping malformed A2S_INFO response from {}", source);
+            }
+        }
+
+        Ok(found.into_iter().collect())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_hosts() {
+            let hosts = hosts("192.168.1.0/30").unwrap();
+            assert_eq!(
+                hosts,
+                vec![
+                    IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+                    IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+                ]
+            );
+            assert_eq!(super::hosts("10.0.0.7/24").unwrap().len(), 254);
+            assert_eq!(super::hosts("10.0.0.7/32").unwrap().len(), 1);
+            assert_eq!(super::hosts("10.0.0.6/31").unwrap().len(), 2);
+            assert_eq!(super::hosts("10.1.0.0/16").unwrap().len(), 65_534);
+
+            for cidr in [
+                "10.0.0.0",
+                "10.0.0.0/33",
+                "localhost/24",
+                "10.0.0.0/15",
+                "10.0.0.0/8",
+                "0.0.0.0/0",
+            ] {
+                assert!(matches!(
+                    super::hosts(cidr),
+                    Err(QueryError::InvalidAddress(_))
+                ));
+            }
+        }
+
+        /// Spawn a server that answers A2S_INFO with a challenge first, then its info.
+        fn info_server(name: &str) -> u16 {
+            info_server_on(name, b'l')
+        }
+
+        /// Spawn a server like [`info_server`], sending the given environment byte.
+        fn info_server_on(name: &str, environment: u8) -> u16 {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let port = socket.local_addr().unwrap().port();
+
+            let mut info = SIMPLE_RESPONSE_HEADER.to_vec();
+            info.extend([0x49, 0x11]);
+            info.extend(name.as_bytes());
+            info.extend(b"\x00de_dust2\x00cstrike\x00Counter-Strike\x00");
+            info.extend(10i16.to_le_bytes());
+            info.extend([5, 24, 0, b'd', environment, 0x00, 0x01]);
+            info.extend(b"1.0.0.0\x00");
+
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                let (_, client) = socket.recv_from(&mut buffer).unwrap();
+                socket
+                    .send_to(&[0xFF, 0xFF, 0xFF, 0xFF, 0x41, 1, 2, 3, 4], client)
+                    .unwrap();
+                let (_, client) = socket.recv_from(&mut buffer).unwrap();
+                socket.send_to(&info, client).unwrap();
+            });
+            port
+        }
+
+        #[test]
+        fn test_scan() {
+            let first = info_server("First");
+            let second = info_server("Second");
+            // Bound but never answers
+            let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let silent = silent.local_addr().unwrap().port();
+            // Answers with an environment byte that is not defined
+            let malformed = info_server_on("Malformed", b'x');
+
+            let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+            let mut found = scan(
+                [localhost],
+                &[first, second, silent, malformed],
+                Duration::from_millis(500),
+            )
+            .unwrap();
+            found.sort_by_key(|(addr, _)| addr.port());
+
+            let mut expected = vec![(first, "First"), (second, "Second")];
+            expected.sort();
+            let found: Vec<(u16, &str)> = found
+                .iter()
+                .map(|(addr, info)| (addr.port(), info.name()))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
+
+pub mod master {
+
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+    use std::time::Duration;
+
+    use crate::error::QueryError;
+    use crate::types::{Byte, PacketReader, PacketWriter};
+    use crate::{PACKET_SIZE, SIMPLE_RESPONSE_HEADER};
+
+    /// Address of Valve's master server for Source and GoldSource servers.
+    pub const MASTER_SERVER: &str = "hl2master.steampowered.com:27011";
+    /// Response header of the master server, 'f' followed by a newline.
+    const RESPONSE_HEADER: [u8; 2] = [0x66, 0x0A];
+    /// Address starting the first page, and ending the last one.
+    const FIRST_ADDRESS: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+
+    /// Region of the servers listed by the master server.
+    ///
+    /// Ref: <https://developer.valvesoftware.com/wiki/Master_Server_Query_Protocol#Region_codes>
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+    pub enum Region {
+        UsEast,
+        UsWest,
+        SouthAmerica,
+        Europe,
+        Asia,
+        Australia,
+        MiddleEast,
+        Africa,
+        /// Every region.
+        #[default]
+        World,
+    }
+
+    impl Region {
+        /// Region code sent in the request.
+        pub fn code(&self) -> Byte {
+            match self {
+                Self::UsEast => 0x00,
+                Self::UsWest => 0x01,
+                Self::SouthAmerica => 0x02,
+                Self::Europe => 0x03,
+                Self::Asia => 0x04,
+                Self::Australia => 0x05,
+                Self::MiddleEast => 0x06,
+                Self::Africa => 0x07,
+                Self::World => 0xFF,
+            }
+        }
+    }
+
+    /// List the servers of `region` matching `filter` with Valve's master server
+    /// (A2M_GET_SERVERS_BATCH2).
+    ///
+    /// The filter is a string of `\key\value` pairs, such as `\appid\440` for Team Fortress 2
+    /// servers, or empty for every server. Each page of addresses is awaited for at most
+    /// `timeout`. The master server rate limits its clients, so a large listing may time out
+    /// before it is complete.
+    ///
+    /// Ref: <https://developer.valvesoftware.com/wiki/Master_Server_Query_Protocol#Filter>
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use valve_server_query::master::{self, Region};
+    /// use valve_server_query::Server;
+    ///
+    /// let addrs = master::servers(Region::Europe, r"\appid\440", Duration::from_secs(5))
+    ///     .expect("List Team Fortress 2 servers in Europe");
+    /// for addr in addrs {
+    ///     let server = Server::new(&addr.to_string()).expect("Connect to server");
+    ///     println!("{addr}: {:?}", server.info().map(|info| info.name().to_string()));
+    /// }
+    /// ```
+    pub fn servers(
+        region: Region,
+        filter: &str,
+        timeout: Duration,
+    ) -> Result<Vec<SocketAddr>, QueryError> {
+        servers_from(MASTER_SERVER, region, filter, timeout)
+    }
+
+    /// Like [`servers`], asking the master server at `master` instead of Valve's.
+    pub fn servers_from(
+        master: &str,
+        region: Region,
+        filter: &str,
+        timeout: Duration,
+    ) -> Result<Vec<SocketAddr>, QueryError> {
+        let master = master
+            .to_socket_addrs()?
+            .find(SocketAddr::is_ipv4)
+            .ok_or_else(|| QueryError::NoMatchingAddress(master.to_string()))?;
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_read_timeout(Some(timeout))?;
+
+        let mut servers: Vec<SocketAddr> = Vec::new();
+        let mut seed = FIRST_ADDRESS;
+        let mut buffer = [0; PACKET_SIZE];
+        loop {
+            socket.send_to(&request(region, seed, filter), master)?;
+            debug!("requested servers from {} after {}", master, seed);
+
+            let packet = loop {
+                let (bytes_returned, source) =
+                    socket.recv_from(&mut buffer).map_err(|e| match e.kind() {
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+                            QueryError::Timeout
+                        }
+                        _ => QueryError::Io(e),
+                    })?;
+                if source == master {
+                    break &buffer[..bytes_returned];
+                }
+            };
+
+            let page = parse_response(packet)?;
+            let last = match page.last() {
+                Some(last) => *last,
+                None => break,
+            };
+            servers.extend(
+                page.iter()
+                    .filter(|addr| **addr != FIRST_ADDRESS)
+                    .map(|addr| SocketAddr::V4(*addr)),
+            );
+            // The last page ends with the first address; a repeated seed would loop forever
+            if last == FIRST_ADDRESS || last == seed {
+                break;
+            }
+            seed = last;
+        }
+
+        Ok(servers)
+    }
+
+    /// Build a request for the servers listed after `seed`.
+    fn request(region: Region, seed: SocketAddrV4, filter: &str) -> Vec<u8> {
+        PacketWriter::new()
+            .write_u8(0x31)
+            .write_u8(region.code())
+            .write_cstring(&seed.to_string())
+            .write_cstring(filter)
+            .into_bytes()
+    }
+
+    /// Read the addresses of a page of the master server response.
+    ///
+    /// Each address is 4 bytes of IP address followed by the port in network byte order,
+    /// unlike the little-endian values of the server queries.
+    fn parse_response(packet: &[u8]) -> Result<Vec<SocketAddrV4>, QueryError> {
+        let mut reader = PacketReader::new(packet);
+        let header = reader.read_bytes(SIMPLE_RESPONSE_HEADER.len() + RESPONSE_HEADER.len())?;
+        if header[..4] != SIMPLE_RESPONSE_HEADER || header[4..] != RESPONSE_HEADER {
+            return Err(QueryError::UnexpectedHeader {
+                expected: RESPONSE_HEADER[0],
+                found: header[4],
+            });
+        }
+
+        let mut servers = Vec::with_capacity(reader.remaining() / 6);
+        while reader.remaining() > 0 {
+            let ip: [u8; 4] = reader.read_bytes(4)?.try_into().expect("4 bytes were read");
+            let port: [u8; 2] = reader.read_bytes(2)?.try_into().expect("2 bytes were read");
+            servers.push(SocketAddrV4::new(ip.into(), u16::from_be_bytes(port)));
+        }
+        Ok(servers)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::thread;
+
+        /// Response page listing `servers`.
+        fn page(servers: &[SocketAddrV4]) -> Vec<u8> {
+            let mut packet = SIMPLE_RESPONSE_HEADER.to_vec();
+            packet.extend(RESPONSE_HEADER);
+            for server in servers {
+                packet.extend(server.ip().octets());
+                packet.extend(server.port().to_be_bytes());
             }
+            packet
+        }
+
+        #[test]
+        fn test_request() {
+            let request = request(Region::Europe, FIRST_ADDRESS, r"\appid\440");
+            assert_eq!(request[..2], [0x31, 0x03]);
+            assert_eq!(&request[2..], b"0.0.0.0:0\0\\appid\\440\0");
+        }
+
+        #[test]
+        fn test_parse_response() {
+            let server = SocketAddrV4::new(Ipv4Addr::new(203, 0, 113, 7), 27015);
+            assert_eq!(
+                parse_response(&page(&[server, FIRST_ADDRESS])).unwrap(),
+                vec![server, FIRST_ADDRESS]
+            );
+            // 27015 is 0x6987, sent in network byte order
+            assert_eq!(page(&[server])[10..], [0x69, 0x87]);
+
+            let truncated = page(&[server]);
+            assert!(matches!(
+                parse_response(&truncated[..truncated.len() - 1]),
+                Err(QueryError::UnexpectedEnd { .. })
+            ));
+            assert!(matches!(
+                parse_response(&[0xFF, 0xFF, 0xFF, 0xFF, 0x49, 0x0A]),
+                Err(QueryError::UnexpectedHeader {
+                    expected: 0x66,
+                    found: 0x49
+                })
+            ));
+        }
+
+        #[test]
+        fn test_servers_paginates() {
+            let first: Vec<SocketAddrV4> = (1..=3)
+                .map(|host| SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, host), 27015))
+                .collect();
+            let second = vec![
+                SocketAddrV4::new(Ipv4Addr::new(10, 0, 1, 1), 27016),
+                FIRST_ADDRESS,
+            ];
+
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let master = socket.local_addr().unwrap();
+            let pages = vec![page(&first), page(&second)];
+            let requests = thread::spawn(move || {
+                let mut buffer = [0; 1024];
+                let mut requests = Vec::new();
+                for page in pages {
+                    let (bytes_returned, client) = socket.recv_from(&mut buffer).unwrap();
+                    requests.push(buffer[..bytes_returned].to_vec());
+                    socket.send_to(&page, client).unwrap();
+                }
+                requests
+            });
+
+            let servers = servers_from(
+                &master.to_string(),
+                Region::World,
+                r"\appid\240",
+                Duration::from_secs(1),
+            )
+            .unwrap();
+            let mut expected: Vec<SocketAddr> = first.iter().copied().map(Into::into).collect();
+            expected.push(second[0].into());
+            assert_eq!(servers, expected);
+
+            let requests = requests.join().unwrap();
+            assert_eq!(
+                requests[0],
+                request(Region::World, FIRST_ADDRESS, r"\appid\240")
+            );
+            assert_eq!(requests[1], request(Region::World, first[2], r"\appid\240"));
         }
     }
 }
 
 pub mod utils {
-    use crate::types::{get_byte, get_long, Byte, Long};
+    use crate::error::QueryError;
+    use crate::server::Engine;
+    use crate::types::{Byte, Long, PacketReader};
+    use crate::MULTI_PACKET_RESPONSE_HEADER;
+
+    /// Header of a split packet, one of the packets of a multi-packet response.
+    ///
+    /// Ref: <https://developer.valvesoftware.com/wiki/Server_queries#Multi-packet_Response_Format>
+    ///
+    /// ```
+    /// use valve_server_query::utils::SplitPacketHeader;
+    /// use valve_server_query::Engine;
+    ///
+    /// let packet = [0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0xE0, 0x04, 0x45];
+    /// let header = SplitPacketHeader::parse(&packet, Engine::Source).unwrap();
+    /// assert_eq!((header.id, header.total, header.number), (7, 3, 1));
+    /// assert_eq!(&packet[header.header_len..], [0x45]);
+    /// ```
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    pub struct SplitPacketHeader {
+        /// Answer ID, shared by every packet of the response.
+        pub id: u32,
+        /// Number of packets of the response.
+        pub total: Byte,
+        /// Number of the packet, starting at 0.
+        pub number: Byte,
+        /// Maximum packet size the server splits at, only sent since the Orange Box.
+        pub size: Option<u16>,
+        /// Whether the response is bzip2 compressed, flagged by the high bit of the answer
+        /// ID. Only Source servers compress responses.
+        pub compressed: bool,
+        /// Number of bytes of the header, where the payload of the packet begins.
+        pub header_len: usize,
+    }
+
+    impl SplitPacketHeader {
+        /// Parse the header of a split packet laid out as sent by `engine`.
+        ///
+        /// Fails with [`QueryError::MalformedPacket`] if the packet is not a split packet, and
+        /// [`QueryError::UnexpectedEnd`] if it is too short for the header.
+        pub fn parse(packet: &[u8], engine: Engine) -> Result<Self, QueryError> {
+            let mut reader = PacketReader::new(packet);
+            if reader.read_bytes(4)? != MULTI_PACKET_RESPONSE_HEADER {
+                return Err(QueryError::MalformedPacket(
+                    "not a split packet".to_string(),
+                ));
+            }
+            let id = reader.read_i32()? as u32;
+
+            let (total, number, size) = match engine {
+                Engine::Source => (
+                    reader.read_u8()?,
+                    reader.read_u8()?,
+                    Some(reader.read_i16()? as u16),
+                ),
+                Engine::SourcePreOrangeBox => (reader.read_u8()?, reader.read_u8()?, None),
+                Engine::GoldSource => {
+                    let packet = reader.read_u8()?;
+                    (packet & 0x0F, packet >> 4, None)
+                }
+            };
+
+            Ok(Self {
+                id,
+                total,
+                number,
+                size,
+                compressed: engine != Engine::GoldSource && id & 0x8000_0000 != 0,
+                header_len: reader.position(),
+            })
+        }
+    }
+
+    /// Answer ID, packet total and packet number of a Source split packet.
+    pub fn get_multipacket_data(buffer: &[u8]) -> Result<(Long, Byte, Byte), QueryError> {
+        let mut reader = PacketReader::new(buffer);
 
-    pub fn get_multipacket_data(buffer: &[u8]) -> (Long, Byte, Byte) {
-        let v = buffer.to_vec();
-        let mut buffer_mut = v.iter();
+        let _header = reader.read_i32()?;
+        let answer_id = reader.read_i32()?;
+        let total = reader.read_u8()?;
+        let packet_id = reader.read_u8()?;
 
-        let _header = get_long(&mut buffer_mut);
-        let answer_id = get_long(&mut buffer_mut);
-        let total = get_byte(&mut buffer_mut);
-        let packet_id = get_byte(&mut buffer_mut);
+        Ok((answer_id, total, packet_id))
+    }
 
-        (answer_id, total, packet_id)
+    /// Answer ID, packet total and packet number of a GoldSource split packet.
+    ///
+    /// The packet number is stored in the upper 4 bits, and the total in the lower 4 bits, of a
+    /// single byte.
+    pub fn get_goldsource_multipacket_data(
+        buffer: &[u8],
+    ) -> Result<(Long, Byte, Byte), QueryError> {
+        let mut reader = PacketReader::new(buffer);
+
+        let _header = reader.read_i32()?;
+        let answer_id = reader.read_i32()?;
+        let packet = reader.read_u8()?;
+        let total = packet & 0x0F;
+        let packet_id = packet >> 4;
+
+        Ok((answer_id, total, packet_id))
     }
 
+    /// Shorten a run of trailing null bytes to a single null byte.
+    #[deprecated(
+        since = "0.4.10",
+        note = "payloads are sliced to the bytes received, so they carry no buffer padding to trim"
+    )]
     pub fn compress_trailing_null_bytes(bytes: &mut Vec<u8>) {
         // No Size
-        if bytes.len() == 0 || bytes.len() == 1 {
+        if bytes.len() <= 1 {
             return;
         }
         // No trailing null bytes
@@ -1075,7 +10027,7 @@ pub mod utils {
 
         // Remove trailing null bytes, then add one null byte
         let mut last = bytes.pop().expect("the next byte exists");
-        while last == 0 && bytes.len() > 0 {
+        while last == 0 && !bytes.is_empty() {
             last = bytes.pop().expect("the next byte exists");
         }
         bytes.push(last);
@@ -1083,10 +10035,80 @@ pub mod utils {
     }
 
     #[cfg(test)]
+    #[allow(deprecated)]
     mod tests {
 
         use super::*;
 
+        #[test]
+        fn test_get_multipacket_data() {
+            let buffer = [
+                0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0xE0, 0x04,
+            ];
+            assert_eq!(get_multipacket_data(&buffer).unwrap(), (7, 3, 1));
+            assert!(matches!(
+                get_multipacket_data(&buffer[..9]),
+                Err(QueryError::UnexpectedEnd {
+                    position: 9,
+                    needed: 1
+                })
+            ));
+        }
+        #[test]
+        fn test_split_packet_header_source() {
+            let packet = [
+                0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x80, 0x03, 0x01, 0xE0, 0x04, 0x45,
+            ];
+            let header = SplitPacketHeader::parse(&packet, Engine::Source).unwrap();
+            assert_eq!(
+                header,
+                SplitPacketHeader {
+                    id: 0x8000_0007,
+                    total: 3,
+                    number: 1,
+                    size: Some(1248),
+                    compressed: true,
+                    header_len: 12,
+                }
+            );
+
+            let header = SplitPacketHeader::parse(&packet, Engine::SourcePreOrangeBox).unwrap();
+            assert_eq!((header.size, header.header_len), (None, 10));
+            assert!(header.compressed);
+
+            assert!(matches!(
+                SplitPacketHeader::parse(&packet[..11], Engine::Source),
+                Err(QueryError::UnexpectedEnd {
+                    position: 10,
+                    needed: 2
+                })
+            ));
+            assert!(matches!(
+                SplitPacketHeader::parse(&[0xFF, 0xFF, 0xFF, 0xFF, 0x49], Engine::Source),
+                Err(QueryError::MalformedPacket(_))
+            ));
+        }
+        #[test]
+        fn test_split_packet_header_goldsource() {
+            let packet = [0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x80, 0x23, 0x45];
+            let header = SplitPacketHeader::parse(&packet, Engine::GoldSource).unwrap();
+            assert_eq!(
+                header,
+                SplitPacketHeader {
+                    id: 0x8000_0007,
+                    total: 3,
+                    number: 2,
+                    size: None,
+                    compressed: false,
+                    header_len: 9,
+                }
+            );
+        }
+        #[test]
+        fn test_get_goldsource_multipacket_data() {
+            let buffer = [0xFF, 0xFF, 0xFF, 0xFE, 0x07, 0x00, 0x00, 0x00, 0x23, 0xFF];
+            assert_eq!(get_goldsource_multipacket_data(&buffer).unwrap(), (7, 3, 2));
+        }
         #[test]
         fn test_compress_null_bytes_basic() {
             let mut bytes: Vec<u8> = vec![1, 2, 3, 0, 0, 0, 0];
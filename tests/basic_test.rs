@@ -5,6 +5,7 @@
 fn test_imports() {
     use valve_server_query;
     use valve_server_query::Server;
+    use valve_server_query::Client;
     use valve_server_query::Player;
     use valve_server_query::Info;
     use valve_server_query::types;
@@ -0,0 +1,56 @@
+//! Allocations made parsing an A2S_INFO response, counted per thread by a wrapping allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use valve_server_query::types::put_cstring;
+use valve_server_query::{Info, InfoRef};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of allocations made by `f` on this thread.
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    let after = ALLOCATIONS.with(Cell::get);
+    drop(value);
+    after - before
+}
+
+#[test]
+fn test_info_ref_allocations() {
+    let mut payload = vec![0x49, 0x11];
+    for field in ["My Server", "de_dust2", "cstrike", "Counter-Strike"] {
+        put_cstring(&mut payload, field);
+    }
+    payload.extend([0x0A, 0x00, 0x05, 0x10, 0x00, b'd', b'l', 0x00, 0x01]);
+    put_cstring(&mut payload, "1.0.0.0");
+    // Extra Data Flag: SourceTV and keywords
+    payload.push(0x40 | 0x20);
+    payload.extend(27020i16.to_le_bytes());
+    put_cstring(&mut payload, "My Server TV");
+    put_cstring(&mut payload, "secure,dust");
+
+    let borrowed = allocations(|| InfoRef::from_bytes(&payload).unwrap());
+    let owned = allocations(|| Info::from_bytes(&payload).unwrap());
+
+    assert_eq!(borrowed, 0);
+    assert!(owned >= 7);
+}